@@ -0,0 +1,167 @@
+/*
+
+Copyright (c) 2020 Pawel Kraszewski. All rights reserved.
+
+Redistribution and use in source and binary forms, with or without modification, are permitted
+provided that the following conditions are met:
+
+    1. Redistributions of source code must retain the above copyright notice, this list of
+       conditions and the following disclaimer.
+    2. Redistributions in binary form must reproduce the above copyright notice, this list of
+       conditions and the following disclaimer in the documentation and/or other materials
+       provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE,
+DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER
+IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT
+OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+*/
+
+//! Classification of errors returned by signal syscalls.
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(feature = "std")]
+use std::io;
+use std::{error, fmt};
+
+#[cfg(not(feature = "std"))]
+use crate::io;
+
+/// An [`io::Error`] from a signal-related call, classified by its `errno`.
+///
+/// Convert with `SignalError::from(err)` to match on the common failure causes instead of raw
+/// `errno` values.
+#[derive(Debug)]
+pub enum SignalError {
+    /// Not allowed to signal the target process (`EPERM`)
+    PermissionDenied,
+    /// Target process or thread doesn't exist (`ESRCH`)
+    NoSuchProcess,
+    /// Invalid signal number or signal that can't be changed (`EINVAL`)
+    InvalidSignal,
+    /// Call interrupted by a signal handler (`EINTR`)
+    Interrupted,
+    /// Any other error
+    Other(io::Error),
+}
+
+/// Signal number not representable as [`Sig`](crate::Sig), see [`Sig::try_from`](crate::Sig::try_from)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InvalidSignal(pub i32);
+
+impl fmt::Display for InvalidSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid or unsupported signal: {}", self.0)
+    }
+}
+
+impl error::Error for InvalidSignal {}
+
+/// Unknown signal name, see [`Sig::from_str`](crate::Sig)
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseSigError(pub String);
+
+impl fmt::Display for ParseSigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid signal name: {}", self.0)
+    }
+}
+
+impl error::Error for ParseSigError {}
+
+impl SignalError {
+    /// Construct the error classified for `errno`, without a failing syscall.
+    ///
+    /// Meant for testing error handling code paths.
+    #[cfg(feature = "test-util")]
+    pub fn from_errno(errno: i32) -> SignalError {
+        io::Error::from_raw_os_error(errno).into()
+    }
+}
+
+impl From<io::Error> for SignalError {
+    fn from(err: io::Error) -> Self {
+        match err.raw_os_error() {
+            Some(libc::EPERM) => SignalError::PermissionDenied,
+            Some(libc::ESRCH) => SignalError::NoSuchProcess,
+            Some(libc::EINVAL) => SignalError::InvalidSignal,
+            Some(libc::EINTR) => SignalError::Interrupted,
+            _ => SignalError::Other(err),
+        }
+    }
+}
+
+impl From<SignalError> for io::Error {
+    fn from(err: SignalError) -> Self {
+        match err {
+            SignalError::PermissionDenied => io::Error::from_raw_os_error(libc::EPERM),
+            SignalError::NoSuchProcess => io::Error::from_raw_os_error(libc::ESRCH),
+            SignalError::InvalidSignal => io::Error::from_raw_os_error(libc::EINVAL),
+            SignalError::Interrupted => io::Error::from_raw_os_error(libc::EINTR),
+            SignalError::Other(err) => err,
+        }
+    }
+}
+
+impl fmt::Display for SignalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignalError::PermissionDenied => f.write_str("not permitted to signal process"),
+            SignalError::NoSuchProcess => f.write_str("no such process"),
+            SignalError::InvalidSignal => f.write_str("invalid signal"),
+            SignalError::Interrupted => f.write_str("interrupted by signal"),
+            SignalError::Other(err) => err.fmt(f),
+        }
+    }
+}
+
+impl error::Error for SignalError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SignalError::Other(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Pid, Sig};
+
+    #[test]
+    fn classify_send_error() {
+        let err = Pid::from(i32::MAX).send(Sig::USR1).unwrap_err();
+        assert!(matches!(SignalError::from(err), SignalError::NoSuchProcess));
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn from_errno() {
+        use SignalError::*;
+
+        assert!(matches!(
+            SignalError::from_errno(libc::EPERM),
+            PermissionDenied
+        ));
+        assert!(matches!(
+            SignalError::from_errno(libc::ESRCH),
+            NoSuchProcess
+        ));
+        assert!(matches!(
+            SignalError::from_errno(libc::EINVAL),
+            InvalidSignal
+        ));
+        assert!(matches!(SignalError::from_errno(libc::EINTR), Interrupted));
+        match SignalError::from_errno(libc::EAGAIN) {
+            Other(err) => assert_eq!(err.raw_os_error(), Some(libc::EAGAIN)),
+            err => panic!("unexpected {:?}", err),
+        }
+    }
+}
@@ -0,0 +1,112 @@
+/*
+
+Copyright (c) 2020 Pawel Kraszewski. All rights reserved.
+
+Redistribution and use in source and binary forms, with or without modification, are permitted
+provided that the following conditions are met:
+
+    1. Redistributions of source code must retain the above copyright notice, this list of
+       conditions and the following disclaimer.
+    2. Redistributions in binary form must reproduce the above copyright notice, this list of
+       conditions and the following disclaimer in the documentation and/or other materials
+       provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE,
+DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER
+IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT
+OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+*/
+
+//! Minimal stand-in for `std::io` error handling, used without `std` feature.
+//!
+//! Errors carry a plain `errno`, so every fallible call of the crate keeps its semantics; with
+//! `std` feature enabled (the default) [`std::io`](https://doc.rust-lang.org/std/io/) is used
+//! instead.
+
+use core::fmt;
+
+/// Result of a fallible call, see [`Error`]
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Category of an [`Error`], mirroring the `std::io::ErrorKind` variants used by the crate
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorKind {
+    /// Invalid argument (`EINVAL`)
+    InvalidInput,
+    /// Data returned by the kernel is not understood
+    InvalidData,
+    /// Operation timed out (`ETIMEDOUT`)
+    TimedOut,
+    /// Call interrupted by a signal handler (`EINTR`)
+    Interrupted,
+    /// Operation would block (`EAGAIN`)
+    WouldBlock,
+    /// Any other error
+    Other,
+}
+
+/// An error with an `errno`, or a kind and message for errors detected by the crate itself
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Error {
+    errno: Option<i32>,
+    kind: ErrorKind,
+    message: &'static str,
+}
+
+impl Error {
+    /// Error from current `errno`
+    pub fn last_os_error() -> Self {
+        Self::from_raw_os_error(unsafe { *libc::__errno_location() })
+    }
+
+    /// Error from an `errno` value
+    pub fn from_raw_os_error(errno: i32) -> Self {
+        let kind = match errno {
+            libc::EINVAL => ErrorKind::InvalidInput,
+            libc::ETIMEDOUT => ErrorKind::TimedOut,
+            libc::EINTR => ErrorKind::Interrupted,
+            libc::EAGAIN => ErrorKind::WouldBlock,
+            _ => ErrorKind::Other,
+        };
+        Self {
+            errno: Some(errno),
+            kind,
+            message: "",
+        }
+    }
+
+    /// Error detected by the crate, not coming from a syscall
+    pub fn new(kind: ErrorKind, message: &'static str) -> Self {
+        Self {
+            errno: None,
+            kind,
+            message,
+        }
+    }
+
+    /// `errno` value of an error coming from a syscall
+    pub fn raw_os_error(&self) -> Option<i32> {
+        self.errno
+    }
+
+    /// Category of the error
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.errno {
+            Some(errno) => write!(f, "os error {}", errno),
+            None => f.write_str(self.message),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
@@ -25,6 +25,9 @@ OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //! A set of tools wrapping Linux' [`libc::sigset_t`] functionality and supporting Rust-firendly
 //! signals and pids definition.
 //!
+//! Core [`Sig`], [`SigSet`] and [`Pid`] operations work without `std` (disable default `std`
+//! feature); errors are then reported with a plain `errno` through the crate's own `io` module.
+//!
 //! # Examples
 //!
 //! ```
@@ -73,11 +76,80 @@ OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //! sigquit.enable_default_handler().expect("Can't enable default handler for SIGQUIT");
 //! ```
 
-use std::{io, mem::MaybeUninit};
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// Without `std`, core items are reached through the same paths
+#[cfg(not(feature = "std"))]
+extern crate core as std;
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(all(test, not(feature = "std")))]
+extern crate std as test_std;
+
+#[cfg(not(feature = "std"))]
+use alloc::{borrow::ToOwned, format, string::String, vec::Vec};
+#[cfg(not(feature = "std"))]
+use libc::c_int as RawFd;
+#[cfg(feature = "std")]
+use std::{
+    cell::Cell,
+    io,
+    os::unix::{io::RawFd, thread::JoinHandleExt},
+    sync::{atomic::AtomicBool, mpsc, Arc},
+    thread::{self, JoinHandle},
+};
+use std::{
+    fmt,
+    iter::FromIterator,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ops::{
+        BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, ControlFlow, Sub, SubAssign,
+    },
+    str::FromStr,
+    sync::atomic::{AtomicI32, AtomicU8, Ordering},
+    time::Duration,
+};
 
 use libc::{
-    c_int, pid_t, pthread_sigmask, sigaddset, sigdelset, sigemptyset, sigfillset, sigismember, sigset_t,
+    c_int, pid_t, pthread_sigmask, sigaddset, sigdelset, sigemptyset, sigfillset, siginfo_t,
+    sigismember, sigset_t,
+};
+
+mod error;
+#[cfg(not(feature = "std"))]
+pub mod io;
+#[cfg(feature = "std")]
+mod procfs;
+#[cfg(feature = "std")]
+mod signalfd;
+#[cfg(feature = "std")]
+mod timer;
+
+/// Re-exports of [`libc`] items used by the crate.
+///
+/// Use them when making own `sigaction`/`pthread_sigmask` calls on top of this crate, to get
+/// exactly the definitions it was built with.
+pub mod raw {
+    pub use libc::{
+        c_int, pid_t, sigaction, sigevent, sighandler_t, siginfo_t, signalfd_siginfo, sigset_t,
+        timespec, SA_NOCLDSTOP, SA_NODEFER, SA_ONSTACK, SA_RESETHAND, SA_RESTART, SA_SIGINFO,
+        SFD_CLOEXEC, SFD_NONBLOCK, SIGEV_SIGNAL, SIGEV_THREAD_ID, SIGRTMAX, SIGRTMIN, SIG_BLOCK,
+        SIG_DFL, SIG_IGN, SIG_SETMASK, SIG_UNBLOCK,
+    };
+}
+
+pub use error::{InvalidSignal, ParseSigError, SignalError};
+#[cfg(feature = "std")]
+pub use procfs::{
+    find_by_name, in_pid_namespace, kill_by_name, proc_signal_status, ProcSignalStatus,
 };
+#[cfg(feature = "std")]
+pub use signalfd::{decode, supports_signalfd, SignalFd, SignalFuture, SignalInfo};
+#[cfg(feature = "std")]
+pub use timer::{PosixTimer, PosixTimerBuilder};
 
 #[cfg(not(target_os = "linux"))]
 compile_error!("sigprocmask and friends are Linux-specific feature");
@@ -87,6 +159,12 @@ compile_error!("sigprocmask and friends are Linux-specific feature");
 pub struct Pid(pid_t);
 
 impl Pid {
+    /// Any child process, valid only as `waitpid` argument
+    pub const ANY_CHILD: Pid = Pid(-1);
+
+    /// Process group of the caller, valid only as `kill`/`waitpid` argument
+    pub const CALLER_GROUP: Pid = Pid(0);
+
     /// Get current process' pid
     pub fn own() -> io::Result<Self> {
         let pid = unsafe { libc::getpid() };
@@ -108,7 +186,22 @@ impl Pid {
     }
 
     /// Send signal to process
+    ///
+    /// With `tracing` feature enabled, a debug event is emitted for every signal sent (and for
+    /// every mask change).
+    ///
+    /// Sentinels ([`Pid::ANY_CHILD`], [`Pid::CALLER_GROUP`] and other non-positive values, which
+    /// `kill` would interpret as groups of processes) are rejected with
+    /// [`io::ErrorKind::InvalidInput`].
     pub fn send(self, sig: Sig) -> io::Result<()> {
+        if self.0 <= 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Not a single process pid",
+            ));
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(pid = self.0, sig = ?sig, "Sending signal");
         let pid = unsafe { libc::kill(self.0, sig.into()) };
         if pid == -1 {
             Err(io::Error::last_os_error())
@@ -116,13 +209,118 @@ impl Pid {
             Ok(())
         }
     }
+
+    /// Check whether the process exists, by sending it signal 0.
+    ///
+    /// Nothing is delivered, the kernel only performs existence and permission checks. `EPERM`
+    /// means the process exists but belongs to someone else, so it's reported as alive. A zombie
+    /// (exited, not yet reaped) child is still alive by this definition. Sentinel pids are rejected
+    /// like in [`Pid::send`].
+    pub fn is_alive(self) -> io::Result<bool> {
+        if self.0 <= 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Not a single process pid",
+            ));
+        }
+        if unsafe { libc::kill(self.0, 0) } == 0 {
+            return Ok(true);
+        }
+        let err = io::Error::last_os_error();
+        match err.raw_os_error() {
+            Some(libc::EPERM) => Ok(true),
+            Some(libc::ESRCH) => Ok(false),
+            _ => Err(err),
+        }
+    }
+
+    /// Wait for the child process to exit or stop, reaping it in the former case.
+    ///
+    /// Wraps `waitpid` with `WUNTRACED`, so stopping the child (e.g. with `SIGSTOP`) also
+    /// returns, as [`ExitStatus::Stopped`]. Interruption by a handled signal is returned as
+    /// [`io::ErrorKind::Interrupted`].
+    pub fn wait(self) -> io::Result<ExitStatus> {
+        self.waitpid(libc::WUNTRACED)
+            .map(|status| status.expect("waitpid without WNOHANG returned no status"))
+    }
+
+    /// Check whether the child process has exited or stopped, without blocking.
+    ///
+    /// Same as [`Pid::wait`], but returns `None` if the child is still running.
+    pub fn try_wait(self) -> io::Result<Option<ExitStatus>> {
+        self.waitpid(libc::WUNTRACED | libc::WNOHANG)
+    }
+
+    /// Wait for the child process like [`Pid::wait`], also returning resources it used.
+    ///
+    /// Wraps `wait4`. Usage covers the child only (not its own, unreaped children); for a
+    /// stopped child it's reported as of the stop.
+    pub fn wait_rusage(self) -> io::Result<(ExitStatus, Rusage)> {
+        let mut status = 0;
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        if unsafe { libc::wait4(self.0, &mut status, libc::WUNTRACED, &mut usage) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok((ExitStatus::from_raw(status)?, Rusage::from(&usage)))
+    }
+
+    fn waitpid(self, options: c_int) -> io::Result<Option<ExitStatus>> {
+        let mut status = 0;
+        match unsafe { libc::waitpid(self.0, &mut status, options) } {
+            -1 => Err(io::Error::last_os_error()),
+            0 => Ok(None),
+            _ => ExitStatus::from_raw(status).map(Some),
+        }
+    }
+
+    /// Queue signal to process with an integer payload, using `sigqueue`.
+    ///
+    /// The receiver gets `value` in `si_value` (see [`SigInfo::value`]). Unlike standard
+    /// signals, real-time ones (see [`Sig::rt`]) are queued, so no payload is lost if several are
+    /// sent before being received. Sentinel pids are rejected like in [`Pid::send`].
+    pub fn send_value(self, sig: Sig, value: i32) -> io::Result<()> {
+        if self.0 <= 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Not a single process pid",
+            ));
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(pid = self.0, sig = ?sig, value, "Queueing signal");
+        // `libc::sigval` only exposes `sival_ptr`; `sival_int` is the `int` at its start
+        let mut sigval = libc::sigval {
+            sival_ptr: std::ptr::null_mut(),
+        };
+        unsafe { std::ptr::write(&mut sigval as *mut libc::sigval as *mut i32, value) };
+        if unsafe { libc::sigqueue(self.0, sig.into(), sigval) } == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Get process' nice value
+    ///
+    /// `getpriority` legitimately returns -1 for a process with nice -1, so `errno` is cleared
+    /// before the call and checked afterwards to tell a real -1 from an error.
+    pub fn nice(self) -> io::Result<i32> {
+        let nice = unsafe {
+            *libc::__errno_location() = 0;
+            libc::getpriority(libc::PRIO_PROCESS as _, self.0 as libc::id_t)
+        };
+        if nice == -1 && unsafe { *libc::__errno_location() } != 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(nice)
+        }
+    }
 }
 
-impl Into<pid_t> for Pid {
+impl From<Pid> for pid_t {
     /// Convert [`Pid`] to [`libc::pid_t`].
     #[inline]
-    fn into(self) -> pid_t {
-        self.0
+    fn from(pid: Pid) -> Self {
+        pid.0
     }
 }
 
@@ -134,319 +332,3916 @@ impl From<pid_t> for Pid {
     }
 }
 
-/// Linux signals
+/// A wrapper for [`libc::pid_t`] identifying a process group.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-#[repr(i32)]
-pub enum Sig {
-    ABRT = libc::SIGABRT,
-    ALRM = libc::SIGALRM,
-    BUS = libc::SIGBUS,
-    CHLD = libc::SIGCHLD,
-    CONT = libc::SIGCONT,
-    FPE = libc::SIGFPE,
-    HUP = libc::SIGHUP,
-    ILL = libc::SIGILL,
-    INT = libc::SIGINT,
-    KILL = libc::SIGKILL,
-    PIPE = libc::SIGPIPE,
-    POLL = libc::SIGPOLL,
-    PROF = libc::SIGPROF,
-    PWR = libc::SIGPWR,
-    QUIT = libc::SIGQUIT,
-    SEGV = libc::SIGSEGV,
-    STKFLT = libc::SIGSTKFLT,
-    STOP = libc::SIGSTOP,
-    SYS = libc::SIGSYS,
-    TERM = libc::SIGTERM,
-    TSTP = libc::SIGTSTP,
-    TTIN = libc::SIGTTIN,
-    TTOU = libc::SIGTTOU,
-    URG = libc::SIGURG,
-    USR1 = libc::SIGUSR1,
-    USR2 = libc::SIGUSR2,
-    VTALRM = libc::SIGVTALRM,
-    WINCH = libc::SIGWINCH,
-    XCPU = libc::SIGXCPU,
-    XFSZ = libc::SIGXFSZ,
-}
+pub struct Pgid(pid_t);
 
-/// Convert `Sig` to `i32` (for example to use with [`libc::*`] crate)
-impl Into<i32> for Sig {
-    #[inline]
-    fn into(self) -> i32 {
-        self as i32
+impl Pgid {
+    /// Get current process' group
+    pub fn own() -> Self {
+        Self(unsafe { libc::getpgrp() })
     }
-}
 
-/// Convert `i32` to `Sig` for valid signals.
-///
-/// Panics if `sig` does not represent a valid signal.
-impl From<i32> for Sig {
-    #[inline]
-    fn from(sig: i32) -> Self {
-        match sig {
-            libc::SIGABRT => Sig::ABRT,
-            libc::SIGALRM => Sig::ALRM,
-            libc::SIGBUS => Sig::BUS,
-            libc::SIGCHLD => Sig::CHLD,
-            libc::SIGCONT => Sig::CONT,
-            libc::SIGFPE => Sig::FPE,
-            libc::SIGHUP => Sig::HUP,
-            libc::SIGILL => Sig::ILL,
-            libc::SIGINT => Sig::INT,
-            libc::SIGKILL => Sig::KILL,
-            libc::SIGPIPE => Sig::PIPE,
-            libc::SIGPOLL => Sig::POLL,
-            libc::SIGPROF => Sig::PROF,
-            libc::SIGPWR => Sig::PWR,
-            libc::SIGQUIT => Sig::QUIT,
-            libc::SIGSEGV => Sig::SEGV,
-            libc::SIGSTKFLT => Sig::STKFLT,
-            libc::SIGSTOP => Sig::STOP,
-            libc::SIGSYS => Sig::SYS,
-            libc::SIGTERM => Sig::TERM,
-            libc::SIGTSTP => Sig::TSTP,
-            libc::SIGTTIN => Sig::TTIN,
-            libc::SIGTTOU => Sig::TTOU,
-            libc::SIGURG => Sig::URG,
-            libc::SIGUSR1 => Sig::USR1,
-            libc::SIGUSR2 => Sig::USR2,
-            libc::SIGVTALRM => Sig::VTALRM,
-            libc::SIGWINCH => Sig::WINCH,
-            libc::SIGXCPU => Sig::XCPU,
-            libc::SIGXFSZ => Sig::XFSZ,
-            s => panic!("Invalid signal {}", s),
+    /// Send signal to every process in the group
+    ///
+    /// Calls `kill` with the negated group id. Group ids 0 and below, as well as 1 (which `kill`
+    /// would negate into "every process we may signal"), are rejected with
+    /// [`io::ErrorKind::InvalidInput`].
+    pub fn send(self, sig: Sig) -> io::Result<()> {
+        if self.0 <= 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Not a single process group",
+            ));
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(pgid = self.0, sig = ?sig, "Sending signal to group");
+        if unsafe { libc::kill(-self.0, sig.into()) } == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
         }
     }
-}
 
-impl Sig {
-    /// Send [`Sig`]nal to process specified by [`libc::pid_t`].
-    pub fn send_to(self, pid: Pid) -> io::Result<()> {
-        pid.send(self)
+    /// Get foreground process group of terminal `tty_fd`
+    pub fn foreground(tty_fd: RawFd) -> io::Result<Self> {
+        let pgid = unsafe { libc::tcgetpgrp(tty_fd) };
+        if pgid == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(Self(pgid))
+        }
+    }
+
+    /// Make process group the foreground one of terminal `tty_fd`
+    pub fn set_foreground(self, tty_fd: RawFd) -> io::Result<()> {
+        if unsafe { libc::tcsetpgrp(tty_fd, self.0) } == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
     }
 }
 
-/// A wrapper for [`libc::sigset_t`]
-pub struct SigSet(sigset_t);
+impl From<Pgid> for pid_t {
+    /// Convert [`Pgid`] to [`libc::pid_t`].
+    #[inline]
+    fn from(pgid: Pgid) -> Self {
+        pgid.0
+    }
+}
 
-impl SigSet {
-    /// Create new, empty [`SigSet`]
+impl From<pid_t> for Pgid {
+    /// Convert [`libc::pid_t`] to [`Pgid`].
     #[inline]
-    pub fn new() -> Self {
-        let mut u_sigset = MaybeUninit::<sigset_t>::uninit();
-        let sigset = unsafe {
-            sigemptyset(u_sigset.as_mut_ptr() as *mut sigset_t);
-            u_sigset.assume_init()
-        };
+    fn from(pgid: pid_t) -> Self {
+        Self(pgid)
+    }
+}
 
-        SigSet(sigset)
+/// A wrapper for [`libc::pid_t`] identifying a thread.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Tid(pid_t);
+
+impl Tid {
+    /// Get current thread's tid
+    pub fn own() -> Self {
+        Self(unsafe { libc::gettid() })
     }
+}
 
-    /// Clear all signals in [`SigSet`]
+impl From<Tid> for pid_t {
+    /// Convert [`Tid`] to [`libc::pid_t`].
     #[inline]
-    pub fn clear(&mut self) -> &mut Self {
-        unsafe {
-            sigemptyset(&mut self.0);
-        }
-        self
+    fn from(tid: Tid) -> Self {
+        tid.0
     }
+}
 
-    /// Set all signals in [`SigSet`]
+impl From<pid_t> for Tid {
+    /// Convert [`libc::pid_t`] to [`Tid`].
     #[inline]
-    pub fn fill(&mut self) -> &mut Self {
-        unsafe {
-            sigfillset(&mut self.0);
-        }
-        self
+    fn from(tid: pid_t) -> Self {
+        Self(tid)
     }
+}
 
-    /// Add a signle [`Sig`] to [`SigSet`].
-    ///
-    /// Re-adding already existing signal does nothing.
-    #[inline]
-    pub fn add(&mut self, sig: Sig) -> &mut Self {
-        unsafe {
-            sigaddset(&mut self.0, sig.into());
+/// Define [`Sig`] and its per-signal lookups from a single table.
+///
+/// Each entry is `VARIANT = libc_constant, wire_code, description, DefaultAction;`, optionally
+/// preceded by doc comments and a `cfg` restricting it. Signals not available on some
+/// architectures (or with `posix-only` feature) are gated once here, rather than in every match.
+macro_rules! signals {
+    ($(
+        $(#[doc = $doc:literal])*
+        $(#[cfg($cfg:meta)])*
+        $variant:ident = $raw:ident, $wire:literal, $desc:literal, $action:ident;
+    )*) => {
+        /// Linux signals
+        #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+        #[repr(i32)]
+        pub enum Sig {
+            $(
+                $(#[doc = $doc])*
+                $(#[cfg($cfg)])*
+                $variant = libc::$raw,
+            )*
+            /// Real-time signal `SIGRTMIN+n`, see [`Sig::rt`].
+            ///
+            /// `SIGRTMIN` is only known at runtime (libc reserves a few signals for itself), so
+            /// the discriminant of this variant is meaningless and the signal number is computed
+            /// on use. The offset can only be created by [`Sig::rt`], which checks its range.
+            RT(RtOffset) = 0,
         }
-        self
-    }
 
-    /// Add a list of [`Sig`]s to [`SigSet`].
-    ///
-    /// Re-adding already existing signals does nothing.
-    pub fn add_many(&mut self, sigs: &[Sig]) -> &mut Self {
-        for &sig in sigs {
-            self.add(sig);
+        impl Sig {
+            /// All standard signals supported on the target architecture.
+            ///
+            /// Real-time signals are not included, as their range is only known at runtime.
+            pub const ALL: &'static [Sig] = &[$($(#[cfg($cfg)])* Sig::$variant,)*];
+
+            /// Encode [`Sig`]nal as a portable, single byte wire code.
+            ///
+            /// The mapping is defined by this crate and doesn't depend on host signal numbers
+            /// (which differ between architectures). It follows x86/ARM Linux numbering:
+            ///
+            /// | Code | Signal |
+            /// |-----:|--------|
+            /// |    1 | `HUP` |
+            /// |    2 | `INT` |
+            /// |    3 | `QUIT` |
+            /// |    4 | `ILL` |
+            /// |    5 | `TRAP` |
+            /// |    6 | `ABRT` |
+            /// |    7 | `BUS` |
+            /// |    8 | `FPE` |
+            /// |    9 | `KILL` |
+            /// |   10 | `USR1` |
+            /// |   11 | `SEGV` |
+            /// |   12 | `USR2` |
+            /// |   13 | `PIPE` |
+            /// |   14 | `ALRM` |
+            /// |   15 | `TERM` |
+            /// |   16 | `STKFLT` |
+            /// |   17 | `CHLD` |
+            /// |   18 | `CONT` |
+            /// |   19 | `STOP` |
+            /// |   20 | `TSTP` |
+            /// |   21 | `TTIN` |
+            /// |   22 | `TTOU` |
+            /// |   23 | `URG` |
+            /// |   24 | `XCPU` |
+            /// |   25 | `XFSZ` |
+            /// |   26 | `VTALRM` |
+            /// |   27 | `PROF` |
+            /// |   28 | `WINCH` |
+            /// |   29 | `POLL` |
+            /// |   30 | `PWR` |
+            /// |   31 | `SYS` |
+            /// | 34+n | `RT(n)` |
+            pub fn to_wire(self) -> u8 {
+                match self {
+                    $($(#[cfg($cfg)])* Sig::$variant => $wire,)*
+                    Sig::RT(offset) => offset.0 + 34,
+                }
+            }
+
+            /// Decode [`Sig`]nal from wire code produced by [`Sig::to_wire`].
+            ///
+            /// Returns `None` for unknown codes and for signals not available on the target
+            /// architecture (or excluded by `posix-only` feature).
+            pub fn from_wire(code: u8) -> Option<Self> {
+                match code {
+                    $($(#[cfg($cfg)])* $wire => Some(Sig::$variant),)*
+                    34..=255 => Sig::rt(code - 34),
+                    _ => None,
+                }
+            }
+
+            /// Get kernel's default action for [`Sig`]nal
+            pub fn default_disposition(self) -> DefaultAction {
+                match self {
+                    $($(#[cfg($cfg)])* Sig::$variant => DefaultAction::$action,)*
+                    Sig::RT(_) => DefaultAction::Term,
+                }
+            }
+
+            /// Canonical short name of [`Sig`]nal, without `SIG` prefix, like `"SEGV"`.
+            ///
+            /// Real-time signals have no fixed name, all of them are `"RT"`; use [`fmt::Display`]
+            /// to get `RTMIN+n`.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $($(#[cfg($cfg)])* Sig::$variant => stringify!($variant),)*
+                    Sig::RT(_) => "RT",
+                }
+            }
+
+            /// Human-readable description of [`Sig`]nal, like `"Segmentation fault"`.
+            ///
+            /// Texts are the ones of glibc's `strsignal` in the C locale, but they're built in, so
+            /// the call is thread-safe and doesn't depend on the current locale.
+            pub fn description(self) -> &'static str {
+                match self {
+                    $($(#[cfg($cfg)])* Sig::$variant => $desc,)*
+                    Sig::RT(_) => "Real-time signal",
+                }
+            }
+
+            /// Convert `i32` to `Sig`, returning `None` if `sig` does not represent a valid
+            /// signal.
+            fn from_raw(sig: i32) -> Option<Self> {
+                match sig {
+                    $($(#[cfg($cfg)])* libc::$raw => Some(Sig::$variant),)*
+                    sig if (libc::SIGRTMIN()..=libc::SIGRTMAX()).contains(&sig) => {
+                        Some(Sig::RT(RtOffset((sig - libc::SIGRTMIN()) as u8)))
+                    }
+                    _ => None,
+                }
+            }
         }
-        self
-    }
+    };
+}
 
-    /// Remove a signle [`Sig`] from [`SigSet`].
-    ///
-    /// Removing already removed signal does nothing.
+/// Offset of a real-time signal from `SIGRTMIN`, see [`Sig::RT`].
+///
+/// It can only be created through [`Sig::rt`] (or by decoding a signal), so it's always within
+/// `SIGRTMIN..=SIGRTMAX` of the running system:
+///
+/// ```compile_fail
+/// use pakr_signals::{RtOffset, Sig};
+///
+/// let sig = Sig::RT(RtOffset(200));
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct RtOffset(u8);
+
+impl RtOffset {
+    /// Get the offset from `SIGRTMIN`
     #[inline]
-    pub fn remove(&mut self, sig: Sig) -> &mut Self {
-        unsafe {
-            sigdelset(&mut self.0, sig.into());
-        }
-        self
+    pub fn get(self) -> u8 {
+        self.0
     }
+}
 
-    /// Remove a list of [`Sig`]s from [`SigSet`].
+signals! {
+    ABRT = SIGABRT, 6, "Aborted", Core;
+    ALRM = SIGALRM, 14, "Alarm clock", Term;
+    BUS = SIGBUS, 7, "Bus error", Core;
+    CHLD = SIGCHLD, 17, "Child exited", Ignore;
+    CONT = SIGCONT, 18, "Continued", Continue;
+    FPE = SIGFPE, 8, "Floating point exception", Core;
+    HUP = SIGHUP, 1, "Hangup", Term;
+    ILL = SIGILL, 4, "Illegal instruction", Core;
+    INT = SIGINT, 2, "Interrupt", Term;
+    KILL = SIGKILL, 9, "Killed", Term;
+    PIPE = SIGPIPE, 13, "Broken pipe", Term;
+    /// Linux-specific, not available with `posix-only` feature
+    #[cfg(not(feature = "posix-only"))]
+    POLL = SIGPOLL, 29, "I/O possible", Term;
+    PROF = SIGPROF, 27, "Profiling timer expired", Term;
+    /// Linux-specific, not available with `posix-only` feature
+    #[cfg(not(feature = "posix-only"))]
+    PWR = SIGPWR, 30, "Power failure", Term;
+    QUIT = SIGQUIT, 3, "Quit", Core;
+    SEGV = SIGSEGV, 11, "Segmentation fault", Core;
+    /// Not available on MIPS and SPARC, nor with `posix-only` feature
+    #[cfg(not(any(
+        feature = "posix-only",
+        target_arch = "mips",
+        target_arch = "mips32r6",
+        target_arch = "mips64",
+        target_arch = "mips64r6",
+        target_arch = "sparc",
+        target_arch = "sparc64"
+    )))]
+    STKFLT = SIGSTKFLT, 16, "Stack fault", Term;
+    STOP = SIGSTOP, 19, "Stopped (signal)", Stop;
+    SYS = SIGSYS, 31, "Bad system call", Core;
+    TERM = SIGTERM, 15, "Terminated", Term;
+    TRAP = SIGTRAP, 5, "Trace/breakpoint trap", Core;
+    TSTP = SIGTSTP, 20, "Stopped", Stop;
+    TTIN = SIGTTIN, 21, "Stopped (tty input)", Stop;
+    TTOU = SIGTTOU, 22, "Stopped (tty output)", Stop;
+    URG = SIGURG, 23, "Urgent I/O condition", Ignore;
+    USR1 = SIGUSR1, 10, "User defined signal 1", Term;
+    USR2 = SIGUSR2, 12, "User defined signal 2", Term;
+    VTALRM = SIGVTALRM, 26, "Virtual timer expired", Term;
+    WINCH = SIGWINCH, 28, "Window changed", Ignore;
+    XCPU = SIGXCPU, 24, "CPU time limit exceeded", Core;
+    XFSZ = SIGXFSZ, 25, "File size limit exceeded", Core;
+}
+
+/// Default action taken by the kernel on a [`Sig`]nal (see `signal(7)`)
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DefaultAction {
+    /// Terminate the process
+    Term,
+    /// Terminate the process and dump core
+    Core,
+    /// Ignore the signal
+    Ignore,
+    /// Stop the process
+    Stop,
+    /// Continue the process if it's stopped
+    Continue,
+}
+
+/// Current disposition of a [`Sig`]nal
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Disposition {
+    /// Default action (`SIG_DFL`), see [`Sig::default_disposition`]
+    Default,
+    /// Signal is ignored (`SIG_IGN`)
+    Ignore,
+    /// A handler is installed
+    Handler,
+}
+
+/// A signal received with [`SigSet::wait_info`], along with its sender
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SigInfo {
+    /// Received signal
+    pub sig: Sig,
+    /// Signal code (`si_code`), e.g. [`libc::SI_USER`] for `kill`
+    pub code: i32,
+    /// Sender's pid, meaningful for signals sent by a process
+    pub pid: Pid,
+    /// Sender's real uid, meaningful for signals sent by a process
+    pub uid: u32,
+    /// Integer sent with `sigqueue`, see [`Pid::send_value`]
+    pub value: i32,
+}
+
+impl SigInfo {
+    /// Decode a [`libc::siginfo_t`] filled by the kernel.
     ///
-    /// Removing already removed signals does nothing.
-    pub fn remove_many(&mut self, sigs: &[Sig]) -> &mut Self {
-        for &sig in sigs {
-            self.remove(sig);
-        }
-        self
+    /// Pid and uid are read through libc's accessors, which know the union layout of the
+    /// platform; for signals where the kernel doesn't fill them they are 0.
+    fn from_raw(info: &siginfo_t) -> io::Result<Self> {
+        let sig = Sig::try_from(info.si_signo).map_err(|_| unsupported_signal(info.si_signo))?;
+        let (pid, uid, value) = unsafe { (info.si_pid(), info.si_uid(), info.si_value()) };
+        Ok(SigInfo {
+            sig,
+            code: info.si_code,
+            pid: Pid(pid),
+            uid,
+            value: unsafe { std::ptr::read(&value as *const libc::sigval as *const i32) },
+        })
     }
+}
 
-    /// Check if [`Sig`]nal is present in [`SigSet`]
-    #[inline]
-    pub fn has(&self, sig: Sig) -> bool {
-        match unsafe { sigismember(&self.0, sig.into()) } {
-            1 => true,
-            _ => false,
+/// How a child process changed state, see [`Pid::wait`]
+#[doc(alias = "WaitStatus")]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ExitStatus {
+    /// Exited normally with the code
+    Exited(i32),
+    /// Terminated by the signal
+    Signaled {
+        /// Terminating signal
+        sig: Sig,
+        /// Whether a core dump was produced (`WCOREDUMP`)
+        core_dumped: bool,
+    },
+    /// Stopped by the signal
+    Stopped(Sig),
+    /// Terminated by a signal not representable as [`Sig`], with its raw number
+    SignaledRaw {
+        /// Terminating signal number
+        signo: i32,
+        /// Whether a core dump was produced (`WCOREDUMP`)
+        core_dumped: bool,
+    },
+    /// Stopped by a signal not representable as [`Sig`], with its raw number
+    StoppedRaw(i32),
+}
+
+/// Resources used by a child process, see [`Pid::wait_rusage`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Rusage {
+    /// CPU time spent in user mode
+    pub user_time: Duration,
+    /// CPU time spent in the kernel
+    pub system_time: Duration,
+    /// Peak resident set size, in kilobytes
+    pub max_rss_kb: u64,
+}
+
+impl From<&libc::rusage> for Rusage {
+    fn from(usage: &libc::rusage) -> Self {
+        let duration = |tv: libc::timeval| {
+            Duration::from_secs(tv.tv_sec.max(0) as u64)
+                + Duration::from_micros(tv.tv_usec.max(0) as u64)
+        };
+        Rusage {
+            user_time: duration(usage.ru_utime),
+            system_time: duration(usage.ru_stime),
+            max_rss_kb: usage.ru_maxrss.max(0) as u64,
         }
     }
+}
 
-    /// Check if [`SigSet`] has any of [`Sig`]nals from the list set
-    pub fn has_any(&self, sigs: &[Sig]) -> bool {
-        for &sig in sigs {
-            if self.has(sig) {
-                return true;
-            }
+impl ExitStatus {
+    /// Describe the status for logs, like `exited with code 0` or `killed by SIGSEGV (core
+    /// dumped)`.
+    pub fn describe(&self) -> String {
+        match self {
+            ExitStatus::Exited(code) => format!("exited with code {}", code),
+            ExitStatus::Signaled {
+                sig,
+                core_dumped: false,
+            } => format!("killed by SIG{}", sig),
+            ExitStatus::Signaled {
+                sig,
+                core_dumped: true,
+            } => format!("killed by SIG{} (core dumped)", sig),
+            ExitStatus::Stopped(sig) => format!("stopped by SIG{}", sig),
+            ExitStatus::SignaledRaw {
+                signo,
+                core_dumped: false,
+            } => format!("killed by signal {}", signo),
+            ExitStatus::SignaledRaw {
+                signo,
+                core_dumped: true,
+            } => format!("killed by signal {} (core dumped)", signo),
+            ExitStatus::StoppedRaw(signo) => format!("stopped by signal {}", signo),
         }
-        false
     }
 
-    /// Check if [`SigSet`] has all of [`Sig`]nals from the list set
-    pub fn has_all(&self, sigs: &[Sig]) -> bool {
-        for &sig in sigs {
-            if !self.has(sig) {
-                return false;
-            }
+    /// Decode status reported by `waitpid`.
+    ///
+    /// The child is already reaped at this point, so a signal unknown to [`Sig`] is kept as a
+    /// raw number rather than failing and losing the status.
+    fn from_raw(status: c_int) -> io::Result<Self> {
+        if libc::WIFEXITED(status) {
+            Ok(ExitStatus::Exited(libc::WEXITSTATUS(status)))
+        } else if libc::WIFSIGNALED(status) {
+            let signo = libc::WTERMSIG(status);
+            let core_dumped = libc::WCOREDUMP(status);
+            Ok(match Sig::try_from(signo) {
+                Ok(sig) => ExitStatus::Signaled { sig, core_dumped },
+                Err(_) => ExitStatus::SignaledRaw { signo, core_dumped },
+            })
+        } else if libc::WIFSTOPPED(status) {
+            let signo = libc::WSTOPSIG(status);
+            Ok(Sig::try_from(signo).map_or(ExitStatus::StoppedRaw(signo), ExitStatus::Stopped))
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Unknown wait status",
+            ))
         }
-        true
     }
+}
 
-    /// Detach [`SigSet`] from default handlers.
-    ///
-    /// Specified signals are ignored by runtime but can be handled by user-defined handlers, for
-    /// example by [`mio-signalfd`].
+/// Convert `Sig` to `i32` (for example to use with [`libc::*`] crate)
+impl From<Sig> for i32 {
     #[inline]
-    pub fn disable_default_handler(&self) -> io::Result<()> {
-        self.set_procmask(libc::SIG_BLOCK)
+    fn from(sig: Sig) -> Self {
+        match sig {
+            Sig::RT(offset) => libc::SIGRTMIN() + i32::from(offset.0),
+            // With `repr(i32)` the discriminant (here the signal number) is stored first
+            _ => unsafe { *(&sig as *const Sig as *const i32) },
+        }
     }
+}
 
-    /// Attach a [`SigSet`] to default handlers.
-    ///
-    /// Specified signals are handled by runtime according to default rules, which may prevent
-    /// signal from reaching user-defined handler.
+/// Convert `i32` to `Sig` for valid signals.
+///
+/// Panics if `sig` does not represent a valid signal, use [`Sig::try_from`] for numbers that may
+/// be invalid. The crate itself never uses this conversion on numbers obtained from the kernel;
+/// unknown signals are skipped or reported instead.
+impl From<i32> for Sig {
     #[inline]
-    pub fn enable_default_handler(&self) -> io::Result<()> {
-        self.set_procmask(libc::SIG_UNBLOCK)
+    fn from(sig: i32) -> Self {
+        Sig::try_from(sig).unwrap()
     }
+}
 
+/// Convert [`Sig`] to [`rustix::process::Signal`].
+#[cfg(feature = "rustix")]
+impl From<Sig> for rustix::process::Signal {
     #[inline]
-    fn set_procmask(&self, action: c_int) -> io::Result<()> {
-        if unsafe { pthread_sigmask(action, self.as_ptr(), std::ptr::null_mut()) } == -1 {
-            Err(io::Error::last_os_error())
-        } else {
-            Ok(())
+    fn from(sig: Sig) -> Self {
+        match sig {
+            // SAFETY: real-time signals of `Sig` start at libc's `SIGRTMIN`, past the ones libc
+            // reserves for itself
+            Sig::RT(_) => unsafe { rustix::process::Signal::from_raw_unchecked(sig.into()) },
+            _ => rustix::process::Signal::from_named_raw(sig.into())
+                .expect("Signal unknown to rustix"),
         }
     }
+}
 
-    /// Create [`SigSet`] pre-populated with list of [`Sig`]s
-    pub fn from(sigs: &[Sig]) -> Self {
-        let mut sigset = Self::new();
-        sigset.add_many(sigs);
-        sigset
-    }
-
-    /// Expose as const pointer to underlying [`libc::sigset_t`]
+/// Convert [`rustix::process::Signal`] to [`Sig`].
+///
+/// Panics if `sig` does not represent a signal known to [`Sig`].
+#[cfg(feature = "rustix")]
+impl From<rustix::process::Signal> for Sig {
     #[inline]
-    pub fn as_ptr(&self) -> *const sigset_t {
-        &self.0
+    fn from(sig: rustix::process::Signal) -> Self {
+        Sig::from(sig.as_raw())
     }
+}
 
-    /// Expose as mut pointer to underlying [`libc::sigset_t`]
-    #[inline]
-    pub fn as_mut_ptr(&mut self) -> *mut sigset_t {
-        &mut self.0
+/// Print short signal name, like `TERM`, or `RTMIN+n` for real-time signals.
+impl fmt::Display for Sig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sig::RT(offset) => write!(f, "RTMIN+{}", offset.0),
+            sig => f.write_str(sig.name()),
+        }
     }
 }
 
+/// Parse signal name (`TERM`, `SIGTERM`, `RTMIN+3`) or decimal number, case-insensitively.
+impl FromStr for Sig {
+    type Err = ParseSigError;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    /// All signals
-    const SIG_ALL: &[Sig] = &[
-        Sig::ABRT,
-        Sig::ALRM,
-        Sig::BUS,
-        Sig::CHLD,
-        Sig::CONT,
-        Sig::FPE,
-        Sig::HUP,
-        Sig::ILL,
-        Sig::INT,
-        Sig::KILL,
-        Sig::PIPE,
-        Sig::POLL,
-        Sig::PROF,
-        Sig::PWR,
-        Sig::QUIT,
-        Sig::SEGV,
-        Sig::STKFLT,
-        Sig::STOP,
-        Sig::SYS,
-        Sig::TERM,
-        Sig::TSTP,
-        Sig::TTIN,
-        Sig::TTOU,
-        Sig::URG,
-        Sig::USR1,
-        Sig::USR2,
-        Sig::VTALRM,
-        Sig::WINCH,
-        Sig::XCPU,
-        Sig::XFSZ,
-    ];
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseSigError(s.to_owned());
+        if let Ok(num) = s.parse::<i32>() {
+            return Sig::try_from(num).map_err(|_| err());
+        }
 
-    #[test]
+        let name = s.to_ascii_uppercase();
+        let name = name.strip_prefix("SIG").unwrap_or(&name);
+        if name == "RTMIN" {
+            return Sig::rt(0).ok_or_else(err);
+        }
+        if let Some(offset) = name.strip_prefix("RTMIN+") {
+            return offset.parse().ok().and_then(Sig::rt).ok_or_else(err);
+        }
+        Sig::ALL
+            .iter()
+            .copied()
+            .find(|sig| sig.name() == name)
+            .ok_or_else(err)
+    }
+}
+
+impl Sig {
+    /// All standard signals supported on the target architecture, same as [`Sig::ALL`]
+    pub const fn all() -> &'static [Sig] {
+        Sig::ALL
+    }
+
+    /// Iterate over all standard signals, followed by all real-time ones.
+    ///
+    /// The real-time range (`SIGRTMIN..=SIGRTMAX`) is computed at runtime, as libc reserves
+    /// a few signals for itself.
+    pub fn all_including_rt() -> impl Iterator<Item = Sig> {
+        let rt_count = libc::SIGRTMAX() - libc::SIGRTMIN() + 1;
+        Sig::ALL
+            .iter()
+            .copied()
+            .chain((0..rt_count).map(|offset| Sig::RT(RtOffset(offset as u8))))
+    }
+
+    /// Iterate over all signals supported on the target architecture, except `exclude`d ones
+    pub fn all_except(exclude: &[Sig]) -> impl Iterator<Item = Sig> + '_ {
+        Sig::ALL
+            .iter()
+            .copied()
+            .filter(move |sig| !exclude.contains(sig))
+    }
+
+    /// Real-time signal `SIGRTMIN+offset`, or `None` if it's past `SIGRTMAX`.
+    pub fn rt(offset: u8) -> Option<Sig> {
+        if i32::from(offset) <= libc::SIGRTMAX() - libc::SIGRTMIN() {
+            Some(Sig::RT(RtOffset(offset)))
+        } else {
+            None
+        }
+    }
+
+    /// Send [`Sig`]nal to process specified by [`libc::pid_t`].
+    pub fn send_to(self, pid: Pid) -> io::Result<()> {
+        pid.send(self)
+    }
+
+    /// Get the canonical number of [`Sig`]nal, independent of host architecture.
+    ///
+    /// Values fixed by POSIX (`HUP`=1, `INT`=2, `QUIT`=3, `ABRT`=6, `KILL`=9, `ALRM`=14,
+    /// `TERM`=15) are the same everywhere. Others follow the generic Linux numbering used on x86
+    /// and ARM, which is also the [`Sig::to_wire`] mapping. On MIPS, SPARC, Alpha and PA-RISC the
+    /// host numbers of `BUS`, `USR1`, `USR2`, `CHLD`, `CONT`, `STOP`, `TSTP`, `URG`, `POLL`,
+    /// `PWR` and `SYS` (among others) differ from these. Real-time signals are numbered from 34,
+    /// the `SIGRTMIN` of glibc.
+    pub fn standard_number(self) -> u8 {
+        self.to_wire()
+    }
+
+    /// Forward [`Sig`]nal to a crossbeam channel.
+    ///
+    /// Runs a [`SignalThread`] (see [`SigSet::spawn_signal_thread`]) sending every received
+    /// signal to `sender`, so it can be `select!`ed along with other events. Signals are dropped
+    /// once all receivers are gone. Stop forwarding with [`SignalThread::shutdown`].
+    #[cfg(all(feature = "std", feature = "crossbeam-channel"))]
+    pub fn forward_to_channel(
+        self,
+        sender: crossbeam_channel::Sender<Sig>,
+    ) -> io::Result<SignalThread> {
+        SigSet::from(&[self]).spawn_signal_thread(move |sig| {
+            let _ = sender.send(sig);
+        })
+    }
+
+    /// Check whether [`Sig`]nal number is valid on the running system.
+    ///
+    /// A few signals (like `SIGSTKFLT` or `SIGPWR`) are not universal across architectures, so
+    /// tools meant to run anywhere can use it before relying on one. Checked by adding the signal
+    /// to a scratch set, which has no side effects.
+    pub fn is_supported(&self) -> bool {
+        let mut sigset = SigSet::new();
+        unsafe { sigaddset(sigset.as_mut_ptr(), (*self).into()) == 0 }
+    }
+
+    /// Check if default action of [`Sig`]nal terminates the process
+    pub fn is_fatal_by_default(self) -> bool {
+        matches!(
+            self.default_disposition(),
+            DefaultAction::Term | DefaultAction::Core
+        )
+    }
+
+    /// Check if delivering [`Sig`]nal now would terminate the process.
+    ///
+    /// Unlike [`Sig::is_fatal_by_default`] it takes current disposition into account: ignored or
+    /// caught signals are not fatal.
+    pub fn is_fatal_now(self) -> io::Result<bool> {
+        let action = get_action(self.into())?;
+        Ok(action.sa_sigaction == libc::SIG_DFL && self.is_fatal_by_default())
+    }
+
+    /// Block [`Sig`]nal in the calling thread, returning whether it was already blocked.
+    pub fn ensure_blocked(self) -> io::Result<bool> {
+        let old = SigSet::from(&[self]).set_procmask(libc::SIG_BLOCK)?;
+        Ok(old.has(self))
+    }
+
+    /// Get current disposition of [`Sig`]nal
+    pub fn current_disposition(self) -> io::Result<Disposition> {
+        let action = get_action(self.into())?;
+        Ok(match action.sa_sigaction {
+            libc::SIG_DFL => Disposition::Default,
+            libc::SIG_IGN => Disposition::Ignore,
+            _ => Disposition::Handler,
+        })
+    }
+
+    /// Check whether [`Sig`]nal would be dropped if the process was init of its PID namespace.
+    ///
+    /// The kernel discards signals sent to init from its own namespace unless a handler is
+    /// installed, so it's `true` for a signal with default or ignore disposition. That includes
+    /// `SIGKILL` and `SIGSTOP`, which init only gets from an ancestor namespace.
+    pub fn will_be_ignored_as_init(&self) -> bool {
+        !matches!(self.current_disposition(), Ok(Disposition::Handler))
+    }
+
+    /// Install `handler` for [`Sig`]nal, returning the previous action.
+    ///
+    /// The handler is installed with `SA_RESTART`, so slow syscalls are resumed rather than
+    /// failing with `EINTR`. For other flags (e.g. `SA_NOCLDSTOP`) or a mask, build the action
+    /// with [`SigAction`] instead. Reinstall the returned action to undo the change.
+    ///
+    /// The handler interrupts arbitrary code, so it may only call async-signal-safe functions
+    /// (see `signal-safety(7)`): no allocation, no locking, no `println!`. Storing to an atomic
+    /// is the usual way to hand the event over to regular code.
+    pub fn set_handler(self, handler: extern "C" fn(c_int)) -> io::Result<SigAction> {
+        SigAction::handler(handler)
+            .flags(libc::SA_RESTART)
+            .install(self)
+    }
+
+    /// Restore the default disposition (`SIG_DFL`) of [`Sig`]nal, returning the previous action.
+    pub fn reset_handler(self) -> io::Result<SigAction> {
+        set_action(self.into(), libc::SIG_DFL, 0).map(SigAction)
+    }
+
+    /// Ignore [`Sig`]nal (`SIG_IGN`).
+    ///
+    /// The usual one-liner for `SIGPIPE`, making writes to a closed pipe or socket fail with
+    /// `EPIPE` instead of killing the process. `SIGKILL` and `SIGSTOP` can't be ignored, the
+    /// kernel reports `EINVAL` for them.
+    pub fn ignore(self) -> io::Result<()> {
+        set_action(self.into(), libc::SIG_IGN, 0).map(|_| ())
+    }
+
+    /// Restore the default disposition (`SIG_DFL`) of [`Sig`]nal.
+    ///
+    /// Same as [`Sig::reset_handler`], without returning the previous action. Fails with
+    /// `EINVAL` for `SIGKILL` and `SIGSTOP`.
+    pub fn reset_default(self) -> io::Result<()> {
+        self.reset_handler().map(|_| ())
+    }
+
+    /// Install `handler` for [`Sig`]nal, unless its disposition was already changed.
+    ///
+    /// Returns whether the handler was installed. Lets a library cooperate with an application
+    /// that installed its own handler (or ignores the signal). Check and installation are two
+    /// separate calls, so concurrent changes of the disposition are not detected.
+    ///
+    /// Note that a process running as init never gets a signal left at default disposition, see
+    /// [`install_init_handlers`].
+    pub fn set_handler_if_default(self, handler: extern "C" fn(c_int)) -> io::Result<bool> {
+        if self.current_disposition()? != Disposition::Default {
+            return Ok(false);
+        }
+        set_action(self.into(), handler as libc::sighandler_t, libc::SA_RESTART)?;
+        Ok(true)
+    }
+
+    /// Raise [`Sig`]nal in the calling thread and have it delivered before returning.
+    ///
+    /// A blocked signal would merely become pending, so it's unblocked for the duration of the
+    /// call (the previous mask is restored afterwards), making the handler (or the default
+    /// action) run synchronously. Don't use it on signals whose disposition terminates the
+    /// process, it would not return.
+    pub fn deliver_now(self) -> io::Result<()> {
+        let _restore = SigBlockGuard(SigSet::get_current()?, PhantomData);
+        SigSet::from(&[self]).enable_default_handler()?;
+        if unsafe { libc::raise(self.into()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Install `handler` for a [`Sig`]nal ignored by default, returning the previous action.
+    ///
+    /// Signals like `SIGURG`, `SIGWINCH` or `SIGCHLD` are discarded by the kernel under the
+    /// default disposition, so nothing ever runs for them unless a handler is installed; blocking
+    /// and waiting works without it, but only while they're blocked. Installed with
+    /// `SA_RESTART`. For `SIGCHLD` consider adding `SA_NOCLDSTOP` with [`SigAction`] directly, to
+    /// skip notifications about stopped children. Signals whose default action is not
+    /// [`DefaultAction::Ignore`] are rejected with [`io::ErrorKind::InvalidInput`].
+    pub fn catch_normally_ignored(self, handler: extern "C" fn(c_int)) -> io::Result<SigAction> {
+        if self.default_disposition() != DefaultAction::Ignore {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Signal is not ignored by default",
+            ));
+        }
+        SigAction::handler(handler)
+            .flags(libc::SA_RESTART)
+            .install(self)
+    }
+
+    /// Record deliveries of [`Sig`]nal for [`last_delivered_signal`].
+    ///
+    /// Installs a handler that only remembers the signal number. It's installed without
+    /// `SA_RESTART`, so a slow syscall interrupted by the signal fails with `EINTR` and
+    /// [`last_delivered_signal`] tells which signal caused it.
+    pub fn track_delivery(self) -> io::Result<()> {
+        set_action(
+            self.into(),
+            record_delivery as extern "C" fn(c_int) as libc::sighandler_t,
+            0,
+        )
+        .map(|_| ())
+    }
+
+    /// Convert `i32` to `Sig`, failing with [`InvalidSignal`] for unknown signals (including the
+    /// ones reserved by libc).
+    ///
+    /// A `TryFrom<i32>` implementation would conflict with the (panicking) `From<i32>` one kept
+    /// for compatibility, hence an inherent method. Use it for numbers coming from the kernel or
+    /// FFI, which may be out of range.
+    #[inline]
+    pub fn try_from(sig: i32) -> Result<Self, InvalidSignal> {
+        Sig::from_raw(sig).ok_or(InvalidSignal(sig))
+    }
+}
+
+/// A wrapper for [`libc::sigset_t`]
+pub struct SigSet(sigset_t);
+
+impl SigSet {
+    /// Create new, empty [`SigSet`]
+    ///
+    /// Underlying [`libc::sigset_t`] is zeroed before `sigemptyset`, so no byte of it is left
+    /// undefined (libc is free to leave bits beyond the kernel's signal range untouched), and
+    /// byte-level comparisons of sets are reliable.
+    #[inline]
+    pub fn new() -> Self {
+        let mut u_sigset = MaybeUninit::<sigset_t>::zeroed();
+        let sigset = unsafe {
+            sigemptyset(u_sigset.as_mut_ptr());
+            u_sigset.assume_init()
+        };
+
+        SigSet(sigset)
+    }
+
+    /// Clear all signals in [`SigSet`]
+    #[inline]
+    pub fn clear(&mut self) -> &mut Self {
+        unsafe {
+            sigemptyset(&mut self.0);
+        }
+        self
+    }
+
+    /// Set all signals in [`SigSet`]
+    #[inline]
+    pub fn fill(&mut self) -> &mut Self {
+        unsafe {
+            sigfillset(&mut self.0);
+        }
+        self
+    }
+
+    /// Create new, full [`SigSet`]
+    pub fn full() -> Self {
+        let mut sigset = Self::new();
+        sigset.fill();
+        sigset
+    }
+
+    /// Create [`SigSet`] of all signals that can be blocked.
+    ///
+    /// It's a full set without `SIGKILL` and `SIGSTOP`, which are silently skipped by the kernel
+    /// anyway, so the set accurately reflects the mask that blocking it would actually produce.
+    pub fn all_blockable() -> Self {
+        let mut sigset = Self::new();
+        sigset.fill().remove(Sig::KILL).remove(Sig::STOP);
+        sigset
+    }
+
+    /// Create a mask for `sigsuspend` that wakes it on any of `sigs`.
+    ///
+    /// `sigsuspend` temporarily replaces the thread's mask with its argument and waits for a
+    /// signal *not* in it, so passing the signals to wait for is a classic bug. This builds the
+    /// inverse: a full set without `sigs`.
+    pub fn for_suspend_waiting_on(sigs: &[Sig]) -> Self {
+        let mut sigset = Self::full();
+        for &sig in sigs {
+            sigset.remove(sig);
+        }
+        sigset
+    }
+
+    /// Create [`SigSet`] of terminal job-control signals: `SIGTSTP`, `SIGTTIN` and `SIGTTOU`.
+    ///
+    /// These are the signals an interactive program (like a shell) has to manage when taking over
+    /// the controlling terminal. `SIGCONT` is not included, as it can't be blocked from resuming a
+    /// stopped process anyway.
+    pub fn terminal_job_control() -> Self {
+        Self::from(&[Sig::TSTP, Sig::TTIN, Sig::TTOU])
+    }
+
+    /// Add a signle [`Sig`] to [`SigSet`].
+    ///
+    /// Re-adding already existing signal does nothing.
+    #[inline]
+    pub fn add(&mut self, sig: Sig) -> &mut Self {
+        unsafe {
+            sigaddset(&mut self.0, sig.into());
+        }
+        self
+    }
+
+    /// Add a list of [`Sig`]s to [`SigSet`].
+    ///
+    /// Re-adding already existing signals does nothing.
+    pub fn add_many(&mut self, sigs: &[Sig]) -> &mut Self {
+        for &sig in sigs {
+            self.add(sig);
+        }
+        self
+    }
+
+    /// Add a list of raw signal numbers to [`SigSet`].
+    ///
+    /// Stops at the first number rejected by `sigaddset`, returning its index and value. Signals
+    /// preceding it remain added.
+    pub fn add_many_raw(&mut self, sigs: &[c_int]) -> Result<&mut Self, (usize, c_int)> {
+        for (idx, &sig) in sigs.iter().enumerate() {
+            if unsafe { sigaddset(&mut self.0, sig) } == -1 {
+                return Err((idx, sig));
+            }
+        }
+        Ok(self)
+    }
+
+    /// Remove a signle [`Sig`] from [`SigSet`].
+    ///
+    /// Removing already removed signal does nothing.
+    #[inline]
+    pub fn remove(&mut self, sig: Sig) -> &mut Self {
+        unsafe {
+            sigdelset(&mut self.0, sig.into());
+        }
+        self
+    }
+
+    /// Remove a list of [`Sig`]s from [`SigSet`].
+    ///
+    /// Removing already removed signals does nothing.
+    pub fn remove_many(&mut self, sigs: &[Sig]) -> &mut Self {
+        for &sig in sigs {
+            self.remove(sig);
+        }
+        self
+    }
+
+    /// Remove a list of raw signal numbers from [`SigSet`].
+    ///
+    /// Stops at the first number rejected by `sigdelset`, returning its index and value. Signals
+    /// preceding it remain removed.
+    pub fn remove_many_raw(&mut self, sigs: &[c_int]) -> Result<&mut Self, (usize, c_int)> {
+        for (idx, &sig) in sigs.iter().enumerate() {
+            if unsafe { sigdelset(&mut self.0, sig) } == -1 {
+                return Err((idx, sig));
+            }
+        }
+        Ok(self)
+    }
+
+    /// Check if [`Sig`]nal is present in [`SigSet`]
+    #[inline]
+    pub fn has(&self, sig: Sig) -> bool {
+        matches!(unsafe { sigismember(&self.0, sig.into()) }, 1)
+    }
+
+    /// Check if raw signal number is present in [`SigSet`]
+    #[inline]
+    fn has_raw(&self, sig: c_int) -> bool {
+        matches!(unsafe { sigismember(&self.0, sig) }, 1)
+    }
+
+    /// Check if [`SigSet`] has any of [`Sig`]nals from the list set
+    pub fn has_any(&self, sigs: &[Sig]) -> bool {
+        for &sig in sigs {
+            if self.has(sig) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Check if [`SigSet`] has all of [`Sig`]nals from the list set
+    pub fn has_all(&self, sigs: &[Sig]) -> bool {
+        for &sig in sigs {
+            if !self.has(sig) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Write [`Sig`]nals present in [`SigSet`] into `buf`, in ascending signal number order.
+    ///
+    /// Returns the number of signals written. If `buf` is too small, only the signals that fit
+    /// are written.
+    pub fn collect_into(&self, buf: &mut [Sig]) -> usize {
+        let mut count = 0;
+        for (slot, sig) in buf.iter_mut().zip(self.iter()) {
+            *slot = sig;
+            count += 1;
+        }
+        count
+    }
+
+    /// Iterate over [`Sig`]nals present in [`SigSet`] (real-time ones included), in ascending
+    /// signal number order.
+    pub fn iter(&self) -> SigSetIter<'_> {
+        SigSetIter {
+            sigset: self,
+            next: 1,
+        }
+    }
+
+    /// Build a set of signals for which `op` of membership in `self` and `other` holds
+    fn combine(&self, other: &SigSet, op: impl Fn(bool, bool) -> bool) -> SigSet {
+        let mut sigset = SigSet::new();
+        for sig in 1..=libc::SIGRTMAX() {
+            if op(self.has_raw(sig), other.has_raw(sig)) {
+                unsafe { sigaddset(sigset.as_mut_ptr(), sig) };
+            }
+        }
+        sigset
+    }
+
+    /// Compute changes needed to turn mask `self` into `target`, as `(to_block, to_unblock)`.
+    ///
+    /// Apply them with [`SigSet::disable_default_handler`] and
+    /// [`SigSet::enable_default_handler`] respectively; signals present in both or neither of
+    /// the sets are left alone.
+    pub fn transition_to(&self, target: &SigSet) -> (SigSet, SigSet) {
+        (target - self, self - target)
+    }
+
+    /// Make the signal mask of the calling thread exactly `target`.
+    ///
+    /// Only signals whose state differs are blocked or unblocked (see [`SigSet::transition_to`]),
+    /// rather than replacing the whole mask at once.
+    pub fn reconcile(target: &SigSet) -> io::Result<()> {
+        let (to_block, to_unblock) = Self::get_current()?.transition_to(target);
+        to_block.disable_default_handler()?;
+        to_unblock.enable_default_handler()
+    }
+
+    /// Count [`Sig`]nals present in [`SigSet`], real-time ones included
+    pub fn count(&self) -> usize {
+        (1..=libc::SIGRTMAX())
+            .filter(|&sig| self.has_raw(sig))
+            .count()
+    }
+
+    /// Check if [`SigSet`] has no [`Sig`]nals at all
+    pub fn is_empty(&self) -> bool {
+        (1..=libc::SIGRTMAX()).all(|sig| !self.has_raw(sig))
+    }
+
+    /// Compute a hash of [`SigSet`] members, usable as a cache key.
+    ///
+    /// Only membership counts (as in [`PartialEq`]), not bits libc leaves unused, so equal sets
+    /// always hash equally. The value is stable across runs. Members are folded into a 64-bit
+    /// mask, which is then scrambled bijectively, so with 64 signals (everywhere but MIPS)
+    /// different sets never collide.
+    pub fn content_hash(&self) -> u64 {
+        let mut hash = (1..=libc::SIGRTMAX())
+            .filter(|&sig| self.has_raw(sig))
+            .fold(0u64, |mask, sig| mask | 1u64.rotate_left(sig as u32 - 1));
+        // splitmix64 finalizer
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        hash ^ (hash >> 31)
+    }
+
+    /// Check if every standard [`Sig`]nal is present in [`SigSet`]
+    pub fn is_full(&self) -> bool {
+        self.has_all(Sig::ALL)
+    }
+
+    /// Get [`Sig`]nals from the list present in [`SigSet`], preserving the list order
+    pub fn which_present(&self, sigs: &[Sig]) -> Vec<Sig> {
+        sigs.iter().copied().filter(|&sig| self.has(sig)).collect()
+    }
+
+    /// Detach [`SigSet`] from default handlers.
+    ///
+    /// Specified signals are ignored by runtime but can be handled by user-defined handlers, for
+    /// example by [`mio-signalfd`].
+    #[inline]
+    pub fn disable_default_handler(&self) -> io::Result<()> {
+        self.set_procmask(libc::SIG_BLOCK).map(|_| ())
+    }
+
+    /// Attach a [`SigSet`] to default handlers.
+    ///
+    /// Specified signals are handled by runtime according to default rules, which may prevent
+    /// signal from reaching user-defined handler.
+    #[inline]
+    pub fn enable_default_handler(&self) -> io::Result<()> {
+        self.set_procmask(libc::SIG_UNBLOCK).map(|_| ())
+    }
+
+    /// Block [`SigSet`] in the calling thread, returning the previous mask.
+    ///
+    /// Meant for chaining with construction, like `SigSet::from(&[Sig::INT]).block_now()?`. The
+    /// returned mask can be restored with [`SigSet::set_mask`].
+    pub fn block_now(&self) -> io::Result<SigSet> {
+        self.set_procmask(libc::SIG_BLOCK)
+    }
+
+    /// Spawn a dedicated thread handling [`SigSet`] with `handler`.
+    ///
+    /// Signals from the set are blocked in the calling thread (so threads spawned from it later
+    /// inherit the mask), while the dedicated thread blocks all the signals and receives ones from
+    /// the set with `sigwait`, calling `handler` for each. Use [`SignalThread::shutdown`] to stop
+    /// it.
+    ///
+    /// The set is available to `handler` through [`current_handled_set`].
+    #[cfg(feature = "std")]
+    pub fn spawn_signal_thread(
+        self,
+        handler: impl FnMut(Sig) + Send + 'static,
+    ) -> io::Result<SignalThread> {
+        self.spawn_signal_thread_pinned(None, handler)
+    }
+
+    /// Spawn a dedicated thread handling [`SigSet`] with `handler`, pinned to `cpu`.
+    ///
+    /// Works like [`SigSet::spawn_signal_thread`], with the thread's affinity restricted to a
+    /// single CPU, so signal processing doesn't disturb the other cores. The affinity is set by
+    /// the thread itself before it starts waiting for signals. Fails (and doesn't leave the thread
+    /// running) if `cpu` isn't available to the process.
+    #[cfg(feature = "std")]
+    pub fn spawn_signal_thread_on_cpu(
+        self,
+        cpu: usize,
+        handler: impl FnMut(Sig) + Send + 'static,
+    ) -> io::Result<SignalThread> {
+        if cpu >= libc::CPU_SETSIZE as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "CPU out of range",
+            ));
+        }
+        self.spawn_signal_thread_pinned(Some(cpu), handler)
+    }
+
+    /// Common part of [`SigSet::spawn_signal_thread`] and [`SigSet::spawn_signal_thread_on_cpu`].
+    ///
+    /// The thread reports its setup result over a channel before entering the `sigwait` loop, so
+    /// a failure is returned here with the thread already finished.
+    #[cfg(feature = "std")]
+    fn spawn_signal_thread_pinned(
+        self,
+        cpu: Option<usize>,
+        mut handler: impl FnMut(Sig) + Send + 'static,
+    ) -> io::Result<SignalThread> {
+        let wakeup = match self.iter().next() {
+            Some(sig) => sig,
+            None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "Empty SigSet")),
+        };
+
+        self.disable_default_handler()?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread_sigset = self.clone();
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("signals".into())
+            .spawn(move || {
+                if let Some(cpu) = cpu {
+                    let mut cpus: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+                    unsafe { libc::CPU_SET(cpu, &mut cpus) };
+                    let rc = unsafe {
+                        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &cpus)
+                    };
+                    if rc != 0 {
+                        let _ = ready_tx.send(Err(io::Error::last_os_error()));
+                        return;
+                    }
+                }
+                if let Err(err) = SigSet::all_blockable().disable_default_handler() {
+                    let _ = ready_tx.send(Err(err));
+                    return;
+                }
+                set_current_handled_set(Some(SigSet(thread_sigset.0)));
+                let _ = ready_tx.send(Ok(()));
+                loop {
+                    let mut sig = 0;
+                    if unsafe { libc::sigwait(thread_sigset.as_ptr(), &mut sig) } != 0 {
+                        break;
+                    }
+                    if thread_stop.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Some(sig) = Sig::from_raw(sig) {
+                        handler(sig);
+                    }
+                }
+            })?;
+
+        let ready = ready_rx
+            .recv()
+            .unwrap_or_else(|_| Err(io::Error::other("Signal thread failed to start")));
+        if let Err(err) = ready {
+            let _ = thread.join();
+            let _ = self.enable_default_handler();
+            return Err(err);
+        }
+
+        Ok(SignalThread {
+            thread,
+            stop,
+            sigset: self,
+            wakeup,
+        })
+    }
+
+    /// Wait for one of [`Sig`]nals from [`SigSet`].
+    ///
+    /// The set should already be blocked in the calling thread's mask, otherwise signals may be
+    /// delivered to their handlers (or default actions) instead of being returned; see
+    /// [`SigSet::wait_checked`] for a variant verifying that. A signal number not representable as
+    /// [`Sig`] is reported as [`io::ErrorKind::InvalidData`].
+    pub fn wait(&self) -> io::Result<Sig> {
+        let mut sig = 0;
+        let rc = unsafe { libc::sigwait(self.as_ptr(), &mut sig) };
+        if rc != 0 {
+            return Err(io::Error::from_raw_os_error(rc));
+        }
+        Sig::try_from(sig).map_err(|_| unsupported_signal(sig))
+    }
+
+    /// Wait for one of [`Sig`]nals from [`SigSet`], reporting who sent it.
+    ///
+    /// Like [`SigSet::wait`], but based on `sigwaitinfo`, so the sender's pid and uid and the
+    /// `si_code` are kept; that tells e.g. a `SIGTERM` from init from one sent by an operator.
+    /// The set should be blocked. Interruption by a handled signal is returned as
+    /// [`io::ErrorKind::Interrupted`].
+    pub fn wait_info(&self) -> io::Result<SigInfo> {
+        let mut info: siginfo_t = unsafe { std::mem::zeroed() };
+        if unsafe { libc::sigwaitinfo(self.as_ptr(), &mut info) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        SigInfo::from_raw(&info)
+    }
+
+    /// Receive [`Sig`]nals from [`SigSet`] in a loop, until `handler` breaks it.
+    ///
+    /// The set is blocked for the duration of the call (the previous mask is restored on
+    /// return) and every signal is received with [`SigSet::wait_info`], so the handler gets the
+    /// sender and the value of signals queued with [`Pid::send_value`]. Interruptions by handled
+    /// signals are retried. Makes an event loop of e.g. a real-time signal based IPC server.
+    pub fn serve<F: FnMut(SigInfo) -> ControlFlow<()>>(&self, mut handler: F) -> io::Result<()> {
+        let _restore = self.block_scoped()?;
+        loop {
+            let info = match self.wait_info() {
+                Ok(info) => info,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            if handler(info).is_break() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Wait for one of [`Sig`]nals from [`SigSet`], checking they are blocked first.
+    ///
+    /// `sigwait` only works reliably for signals blocked in the calling thread; unblocked ones may
+    /// get delivered to their handlers (or default actions) instead. Returns
+    /// [`io::ErrorKind::InvalidInput`] if any signal from the set is not blocked.
+    pub fn wait_checked(&self) -> io::Result<Sig> {
+        let current = Self::get_current()?;
+        if (1..=libc::SIGRTMAX()).any(|sig| self.has_raw(sig) && !current.has_raw(sig)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Waiting for unblocked signal",
+            ));
+        }
+        self.wait()
+    }
+
+    /// Replace signal mask of the calling thread with [`SigSet`], returning the previous mask.
+    ///
+    /// Unlike [`SigSet::disable_default_handler`] and [`SigSet::enable_default_handler`], which
+    /// change the mask relative to the current one, the new mask is exactly `self`, regardless
+    /// of what was inherited.
+    pub fn set_mask(&self) -> io::Result<SigSet> {
+        self.set_procmask(libc::SIG_SETMASK)
+    }
+
+    /// Block [`Sig`]nals from [`SigSet`] until the returned guard is dropped.
+    ///
+    /// The whole previous mask of the calling thread is restored on drop, also on early return or
+    /// panic, so signals that were already blocked stay blocked.
+    pub fn block_scoped(&self) -> io::Result<SigBlockGuard> {
+        let guard = SigBlockGuard(Self::get_current()?, PhantomData);
+        self.disable_default_handler()?;
+        Ok(guard)
+    }
+
+    /// Receive a single [`Sig`]nal from [`SigSet`], leaving the signal mask as it was.
+    ///
+    /// The set is blocked for the duration of the call (as `sigwait` requires) and the previous
+    /// mask is restored afterwards on every path, so a signal not blocked before is again
+    /// delivered to its handler once this returns.
+    pub fn receive_once(&self) -> io::Result<Sig> {
+        let _restore = self.block_scoped()?;
+        self.wait_checked()
+    }
+
+    /// Wait up to `timeout` for one of [`Sig`]nals from [`SigSet`], returning `None` on timeout.
+    ///
+    /// Signals must be blocked. Timeouts beyond what `timespec` can hold are clamped to the
+    /// longest representable one. Interruption by a handled signal (`EINTR`) is not retried, but
+    /// returned as [`io::ErrorKind::Interrupted`], so the caller can recompute the remaining time.
+    pub fn wait_timeout(&self, timeout: Duration) -> io::Result<Option<Sig>> {
+        let timeout = to_timespec(timeout);
+        let sig = unsafe { libc::sigtimedwait(self.as_ptr(), std::ptr::null_mut(), &timeout) };
+        if sig == -1 {
+            let err = io::Error::last_os_error();
+            return match err.raw_os_error() {
+                Some(libc::EAGAIN) => Ok(None),
+                _ => Err(err),
+            };
+        }
+        Sig::try_from(sig)
+            .map(Some)
+            .map_err(|_| unsupported_signal(sig))
+    }
+
+    /// Wait up to `timeout` for one of [`Sig`]nals from [`SigSet`].
+    ///
+    /// Signals must be blocked. Timeout is reported as [`io::ErrorKind::TimedOut`], interruption
+    /// by a handled signal as [`io::ErrorKind::Interrupted`].
+    pub fn wait_timeout_err(&self, timeout: Duration) -> io::Result<Sig> {
+        self.wait_timeout(timeout)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "Signal wait timed out"))
+    }
+
+    /// Wait until `sig` is received `n` times.
+    ///
+    /// Signals are received with `sigwait` on the whole [`SigSet`], so they must be blocked. Other
+    /// signals from the set are consumed and ignored. Returns immediately if `n` is 0.
+    pub fn wait_count(&self, sig: Sig, n: usize) -> io::Result<()> {
+        let mut count = 0;
+        while count < n {
+            let mut received = 0;
+            let rc = unsafe { libc::sigwait(self.as_ptr(), &mut received) };
+            if rc != 0 {
+                return Err(io::Error::from_raw_os_error(rc));
+            }
+            if received == sig.into() {
+                count += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Consume all pending [`Sig`]nals from [`SigSet`], returning the distinct ones.
+    ///
+    /// Signals must be blocked. Standard signals don't queue, so each is naturally reported once;
+    /// queued real-time signals are consumed entirely but also reported once.
+    pub fn drain_unique(&self) -> io::Result<SigSet> {
+        let mut drained = SigSet::new();
+        let timeout = to_timespec(Duration::ZERO);
+        loop {
+            let sig = unsafe { libc::sigtimedwait(self.as_ptr(), std::ptr::null_mut(), &timeout) };
+            if sig == -1 {
+                let err = io::Error::last_os_error();
+                match err.raw_os_error() {
+                    Some(libc::EAGAIN) => return Ok(drained),
+                    Some(libc::EINTR) => continue,
+                    _ => return Err(err),
+                }
+            }
+            unsafe { sigaddset(&mut drained.0, sig) };
+        }
+    }
+
+    /// Check if any [`Sig`]nal from [`SigSet`] is pending.
+    ///
+    /// Useful to decide whether draining is needed, without enumerating the whole pending set.
+    pub fn is_any_pending(&self) -> io::Result<bool> {
+        let pending = Self::pending()?;
+        Ok((1..=libc::SIGRTMAX()).any(|sig| self.has_raw(sig) && pending.has_raw(sig)))
+    }
+
+    /// Read signals pending for the calling thread (or the whole process).
+    ///
+    /// A signal is pending when it was raised while blocked, so after a critical section with
+    /// signals blocked it tells which of them arrived in the meantime.
+    pub fn pending() -> io::Result<Self> {
+        let mut sigset = Self::new();
+        if unsafe { libc::sigpending(sigset.as_mut_ptr()) } == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(sigset)
+        }
+    }
+
+    /// Read signal mask of the calling thread.
+    ///
+    /// Use it to save the mask before temporarily changing it, and restore it later with
+    /// [`SigSet::reconcile`].
+    pub fn get_current() -> io::Result<Self> {
+        let mut sigset = Self::new();
+        let rc = unsafe { pthread_sigmask(libc::SIG_BLOCK, std::ptr::null(), sigset.as_mut_ptr()) };
+        if rc != 0 {
+            Err(io::Error::from_raw_os_error(rc))
+        } else {
+            Ok(sigset)
+        }
+    }
+
+    /// Count [`Sig`]nals from [`SigSet`] currently blocked in the calling thread
+    pub fn blocked_count(&self) -> io::Result<usize> {
+        Ok((self & &Self::get_current()?).count())
+    }
+
+    /// Get standard [`Sig`]nals from [`SigSet`] not blocked in the calling thread.
+    ///
+    /// These would still reach their handlers (or default actions) when delivered to the thread.
+    /// Call it on [`SigSet::full`] to see the effect of [`SigSet::disable_default_handler`] on
+    /// the whole mask.
+    pub fn still_default(&self) -> io::Result<SigSet> {
+        let current = Self::get_current()?;
+        let mut sigset = SigSet::new();
+        for &sig in Sig::ALL {
+            if self.has(sig) && !current.has(sig) {
+                sigset.add(sig);
+            }
+        }
+        Ok(sigset)
+    }
+
+    /// Change the calling thread's mask by [`SigSet`] with `how` (`SIG_BLOCK`, `SIG_UNBLOCK` or
+    /// `SIG_SETMASK`), returning the previous mask.
+    ///
+    /// Every mask change of the crate goes through here, so each emits a `tracing` event.
+    #[inline]
+    fn set_procmask(&self, how: c_int) -> io::Result<SigSet> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(how, "Changing signal mask");
+        self.set_procmask_untraced(how)
+    }
+
+    /// Same as [`SigSet::set_procmask`], without the `tracing` event, so only async-signal-safe
+    /// calls are made (for a freshly forked child).
+    fn set_procmask_untraced(&self, how: c_int) -> io::Result<SigSet> {
+        let mut old = SigSet::new();
+        // Returns the error number rather than setting `errno`
+        let rc = unsafe { pthread_sigmask(how, self.as_ptr(), old.as_mut_ptr()) };
+        if rc != 0 {
+            Err(io::Error::from_raw_os_error(rc))
+        } else {
+            Ok(old)
+        }
+    }
+
+    /// Create [`SigSet`] pre-populated with list of [`Sig`]s
+    pub fn from(sigs: &[Sig]) -> Self {
+        let mut sigset = Self::new();
+        sigset.add_many(sigs);
+        sigset
+    }
+
+    /// Create [`SigSet`] from raw signal numbers, skipping invalid ones.
+    ///
+    /// Returns the set together with the numbers rejected by `sigaddset`, in input order.
+    pub fn from_raw_iter(iter: impl IntoIterator<Item = c_int>) -> (Self, Vec<c_int>) {
+        let mut sigset = Self::new();
+        let mut rejected = Vec::new();
+        for sig in iter {
+            if unsafe { sigaddset(&mut sigset.0, sig) } == -1 {
+                rejected.push(sig);
+            }
+        }
+        (sigset, rejected)
+    }
+
+    /// Expose as const pointer to underlying [`libc::sigset_t`]
+    #[inline]
+    pub fn as_ptr(&self) -> *const sigset_t {
+        &self.0
+    }
+
+    /// Expose as the temporary mask argument of `pselect`/`ppoll`
+    ///
+    /// Same as [`SigSet::as_ptr`]. The pointer points into the [`SigSet`] itself, so it's valid
+    /// only as long as the [`SigSet`] is neither moved nor dropped; keep the set in a binding
+    /// that outlives the call.
+    ///
+    /// ```
+    /// use pakr_signals::*;
+    ///
+    /// // Wait with all signals but SIGINT blocked
+    /// let mut mask = SigSet::new();
+    /// mask.fill().remove(Sig::INT);
+    ///
+    /// let timeout = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    /// let rc = unsafe {
+    ///     libc::pselect(
+    ///         0,
+    ///         std::ptr::null_mut(),
+    ///         std::ptr::null_mut(),
+    ///         std::ptr::null_mut(),
+    ///         &timeout,
+    ///         mask.as_pselect_mask(),
+    ///     )
+    /// };
+    /// assert_eq!(rc, 0);
+    /// ```
+    #[inline]
+    pub fn as_pselect_mask(&self) -> *const sigset_t {
+        self.as_ptr()
+    }
+
+    /// Expose [`SigSet`] in the layout raw syscalls (`rt_sigprocmask`, `rt_sigtimedwait`,
+    /// `signalfd4`, ...) expect, as bytes and their size.
+    ///
+    /// libc's [`libc::sigset_t`] is 128 bytes long, leaving room for future signals, but the
+    /// kernel's `sigset_t` only has `_NSIG / 8` bytes (8 on most architectures, 16 on MIPS), and
+    /// syscalls reject any other size with `EINVAL`. Both are arrays of `unsigned long` words, so
+    /// the kernel set is simply the leading part of libc's one. Pass the size as the `sigsetsize`
+    /// argument; don't use it with libc wrappers, which take the whole [`libc::sigset_t`].
+    pub fn as_kernel_sigset(&self) -> (&[u8], usize) {
+        // `SIGRTMAX` is the last signal, `_NSIG - 1`
+        let size = (libc::SIGRTMAX() as usize + 1) / 8;
+        let bytes = unsafe {
+            std::slice::from_raw_parts(self.as_ptr() as *const u8, std::mem::size_of::<sigset_t>())
+        };
+        (&bytes[..size], size)
+    }
+
+    /// Expose as mut pointer to underlying [`libc::sigset_t`]
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut sigset_t {
+        &mut self.0
+    }
+}
+
+/// List member names, like `SigSet { INT, TERM, RTMIN+2 }`
+impl fmt::Debug for SigSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SigSet {")?;
+        for (i, sig) in self.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { " " } else { ", " }, sig)?;
+        }
+        f.write_str(if self.is_empty() { "}" } else { " }" })
+    }
+}
+
+/// Collect [`Sig`]nals into a new set
+impl FromIterator<Sig> for SigSet {
+    fn from_iter<I: IntoIterator<Item = Sig>>(iter: I) -> Self {
+        let mut sigset = SigSet::new();
+        sigset.extend(iter);
+        sigset
+    }
+}
+
+/// Add [`Sig`]nals to the set, keeping the ones already present
+impl Extend<Sig> for SigSet {
+    fn extend<I: IntoIterator<Item = Sig>>(&mut self, iter: I) {
+        for sig in iter {
+            self.add(sig);
+        }
+    }
+}
+
+/// Empty set, same as [`SigSet::new`]
+impl Default for SigSet {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for SigSet {
+    fn clone(&self) -> Self {
+        SigSet(self.0)
+    }
+}
+
+/// Sets are equal when they have the same members, regardless of bits libc may leave unused in
+/// [`libc::sigset_t`].
+impl PartialEq for SigSet {
+    fn eq(&self, other: &SigSet) -> bool {
+        (1..=libc::SIGRTMAX()).all(|sig| self.has_raw(sig) == other.has_raw(sig))
+    }
+}
+
+impl Eq for SigSet {}
+
+/// Iterator over [`Sig`]nals present in a [`SigSet`], see [`SigSet::iter`]
+pub struct SigSetIter<'a> {
+    sigset: &'a SigSet,
+    next: c_int,
+}
+
+impl Iterator for SigSetIter<'_> {
+    type Item = Sig;
+
+    fn next(&mut self) -> Option<Sig> {
+        while self.next <= libc::SIGRTMAX() {
+            let sig = self.next;
+            self.next += 1;
+            if self.sigset.has_raw(sig) {
+                if let Some(sig) = Sig::from_raw(sig) {
+                    return Some(sig);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Implement a set operator (and its assigning variant) on [`SigSet`]s by combining membership
+/// of every signal with `$op`
+macro_rules! sigset_operator {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $op:expr) => {
+        impl $trait for &SigSet {
+            type Output = SigSet;
+
+            fn $method(self, other: &SigSet) -> SigSet {
+                self.combine(other, $op)
+            }
+        }
+
+        impl $trait for SigSet {
+            type Output = SigSet;
+
+            fn $method(self, other: SigSet) -> SigSet {
+                self.combine(&other, $op)
+            }
+        }
+
+        impl $assign_trait<&SigSet> for SigSet {
+            fn $assign_method(&mut self, other: &SigSet) {
+                *self = self.combine(other, $op);
+            }
+        }
+
+        impl $assign_trait for SigSet {
+            fn $assign_method(&mut self, other: SigSet) {
+                *self = self.combine(&other, $op);
+            }
+        }
+    };
+}
+
+sigset_operator!(BitOr, bitor, BitOrAssign, bitor_assign, |a, b| a || b);
+sigset_operator!(BitAnd, bitand, BitAndAssign, bitand_assign, |a, b| a && b);
+sigset_operator!(Sub, sub, SubAssign, sub_assign, |a, b| a && !b);
+sigset_operator!(BitXor, bitxor, BitXorAssign, bitxor_assign, |a, b| a != b);
+
+impl<'a> IntoIterator for &'a SigSet {
+    type Item = Sig;
+    type IntoIter = SigSetIter<'a>;
+
+    fn into_iter(self) -> SigSetIter<'a> {
+        self.iter()
+    }
+}
+
+/// Signal action as used by `sigaction(2)`: a handler, its flags and its `sa_mask`.
+///
+/// Built with [`SigAction::handler`] and installed with [`SigAction::install`], which returns the
+/// previous action, so it can be inspected or reinstalled later.
+#[derive(Clone, Copy)]
+pub struct SigAction(libc::sigaction);
+
+impl SigAction {
+    /// Start building an action calling `handler`, with no flags and an empty `sa_mask`.
+    pub fn handler(handler: extern "C" fn(c_int)) -> Self {
+        let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+        action.sa_sigaction = handler as libc::sighandler_t;
+        action.sa_mask = SigSet::new().0;
+        SigAction(action)
+    }
+
+    /// Set `SA_*` flags of the action.
+    pub fn flags(mut self, flags: c_int) -> Self {
+        self.0.sa_flags = flags;
+        self
+    }
+
+    /// Block [`Sig`]nals from `mask` while the handler runs (in addition to the handled signal).
+    ///
+    /// Use it to keep handlers sharing state from interrupting each other.
+    pub fn mask(mut self, mask: &SigSet) -> Self {
+        self.0.sa_mask = mask.0;
+        self
+    }
+
+    /// Signals blocked while the handler runs (`sa_mask`).
+    pub fn blocked(&self) -> SigSet {
+        SigSet(self.0.sa_mask)
+    }
+
+    /// Install the action for `sig`, returning the previous one.
+    ///
+    /// A process running as init must install explicit handlers for signals it wants to get, see
+    /// [`install_init_handlers`].
+    pub fn install(&self, sig: Sig) -> io::Result<SigAction> {
+        let mut old: libc::sigaction = unsafe { std::mem::zeroed() };
+        if unsafe { libc::sigaction(sig.into(), &self.0, &mut old) } == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(SigAction(old))
+        }
+    }
+}
+
+/// Dispositions of all signals, captured to be restored later.
+///
+/// Covers every signal that can be caught, real-time ones included. Useful for running code
+/// (e.g. a plugin) which installs its own handlers, and reverting whatever it did afterwards.
+pub struct DispositionSnapshot(Vec<(c_int, libc::sigaction)>);
+
+impl DispositionSnapshot {
+    /// Capture dispositions of all catchable signals.
+    pub fn capture() -> io::Result<Self> {
+        let mut actions = Vec::new();
+        for sig in 1..=libc::SIGRTMAX() {
+            if sig == libc::SIGKILL || sig == libc::SIGSTOP {
+                continue;
+            }
+            match get_action(sig) {
+                Ok(action) => actions.push((sig, action)),
+                Err(err) if err.raw_os_error() == Some(libc::EINVAL) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(DispositionSnapshot(actions))
+    }
+
+    /// Reinstall captured dispositions.
+    ///
+    /// Signals reserved by libc (which can't be changed) are silently skipped.
+    pub fn restore(&self) -> io::Result<()> {
+        for (sig, action) in &self.0 {
+            if unsafe { libc::sigaction(*sig, action, std::ptr::null_mut()) } == -1 {
+                let err = io::Error::last_os_error();
+                if err.raw_os_error() != Some(libc::EINVAL) {
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A dedicated signal handling thread, see [`SigSet::spawn_signal_thread`]
+///
+/// It's `Send`, but [`SignalThread::shutdown`] unblocks signals in the thread calling it, which
+/// is normally the one that spawned it.
+#[cfg(feature = "std")]
+pub struct SignalThread {
+    thread: JoinHandle<()>,
+    stop: Arc<AtomicBool>,
+    sigset: SigSet,
+    wakeup: Sig,
+}
+
+#[cfg(feature = "std")]
+impl SignalThread {
+    /// Stop the signal thread and wait for it to finish.
+    ///
+    /// Handled signals are unblocked back in the calling thread.
+    pub fn shutdown(self) -> io::Result<()> {
+        self.stop.store(true, Ordering::SeqCst);
+        let rc = unsafe { libc::pthread_kill(self.thread.as_pthread_t(), self.wakeup.into()) };
+        if rc != 0 && rc != libc::ESRCH {
+            return Err(io::Error::from_raw_os_error(rc));
+        }
+        self.thread
+            .join()
+            .map_err(|_| io::Error::other("Signal handler panicked"))?;
+        self.sigset.enable_default_handler()
+    }
+}
+
+/// Restores the signal mask of the current thread when dropped, see [`SigSet::block_scoped`].
+///
+/// The mask is restored on the thread dropping the guard, so it's not `Send`.
+#[must_use = "signals are unblocked again when the guard is dropped"]
+pub struct SigBlockGuard(SigSet, PhantomData<*const ()>);
+
+impl SigBlockGuard {
+    /// Restore the mask now, reporting a failure that dropping the guard would ignore.
+    #[cfg(feature = "std")]
+    fn restore(self) -> io::Result<()> {
+        let result = self.0.set_procmask(libc::SIG_SETMASK).map(|_| ());
+        std::mem::forget(self);
+        result
+    }
+}
+
+impl Drop for SigBlockGuard {
+    fn drop(&mut self) {
+        let _ = self.0.set_procmask(libc::SIG_SETMASK);
+    }
+}
+
+/// [`SigSet`] built from a list of [`Sig`]nals on first use, see [`sigset!`]
+///
+/// Filling a [`libc::sigset_t`] requires libc calls, which can't run at compile time, so the set
+/// is built once on first access and cached. It dereferences to [`SigSet`].
+#[cfg(feature = "std")]
+pub struct LazySigSet {
+    sigs: &'static [Sig],
+    sigset: std::sync::OnceLock<SigSet>,
+}
+
+#[cfg(feature = "std")]
+impl LazySigSet {
+    /// Create a set of `sigs`, to be built on first access. Usually invoked through [`sigset!`].
+    pub const fn new(sigs: &'static [Sig]) -> Self {
+        LazySigSet {
+            sigs,
+            sigset: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// Get the [`SigSet`], building it on the first call
+    pub fn get(&self) -> &SigSet {
+        self.sigset.get_or_init(|| SigSet::from(self.sigs))
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::ops::Deref for LazySigSet {
+    type Target = SigSet;
+
+    fn deref(&self) -> &SigSet {
+        self.get()
+    }
+}
+
+/// Define a [`LazySigSet`] from a list of [`Sig`] variant names.
+///
+/// ```
+/// use pakr_signals::{sigset, LazySigSet, Sig};
+///
+/// static SHUTDOWN: LazySigSet = sigset!(INT, TERM, HUP);
+///
+/// assert!(SHUTDOWN.has(Sig::TERM));
+/// assert!(!SHUTDOWN.has(Sig::USR1));
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! sigset {
+    ($($sig:ident),* $(,)?) => {
+        $crate::LazySigSet::new(&[$($crate::Sig::$sig),*])
+    };
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    /// Signals handled by the current thread, see [`current_handled_set`]
+    static HANDLED: Cell<Option<sigset_t>> = const { Cell::new(None) };
+}
+
+/// Record [`SigSet`] handled by the current thread.
+///
+/// It's a bookkeeping aid for signal thread abstractions, it doesn't change any mask. Pass `None`
+/// to clear it.
+#[cfg(feature = "std")]
+pub fn set_current_handled_set(sigset: Option<SigSet>) {
+    HANDLED.with(|handled| handled.set(sigset.map(|sigset| sigset.0)));
+}
+
+/// Get [`SigSet`] recorded by [`set_current_handled_set`] for the current thread.
+#[cfg(feature = "std")]
+pub fn current_handled_set() -> Option<SigSet> {
+    HANDLED.with(|handled| handled.get().map(SigSet))
+}
+
+/// Number of the last signal caught by a [`Sig::track_delivery`] handler
+static LAST_DELIVERED: AtomicI32 = AtomicI32::new(0);
+
+extern "C" fn record_delivery(sig: c_int) {
+    LAST_DELIVERED.store(sig, Ordering::Relaxed);
+}
+
+/// Get the last [`Sig`]nal delivered to the process.
+///
+/// Only signals tracked with [`Sig::track_delivery`] are accounted for, as delivery of a signal
+/// can't be observed without a handler. Returns `None` if no tracked signal was delivered yet.
+pub fn last_delivered_signal() -> Option<Sig> {
+    Sig::from_raw(LAST_DELIVERED.load(Ordering::Relaxed))
+}
+
+/// Convert [`Duration`] to [`libc::timespec`], saturating values out of range.
+fn to_timespec(duration: Duration) -> libc::timespec {
+    libc::timespec {
+        tv_sec: duration.as_secs().min(libc::time_t::MAX as u64) as libc::time_t,
+        tv_nsec: duration.subsec_nanos() as _,
+    }
+}
+
+/// Get current action for `sig`.
+fn get_action(sig: c_int) -> io::Result<libc::sigaction> {
+    let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+    if unsafe { libc::sigaction(sig, std::ptr::null(), &mut action) } == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(action)
+    }
+}
+
+/// Install `handler` for `sig` with `flags`, returning the previous action.
+fn set_action(
+    sig: c_int,
+    handler: libc::sighandler_t,
+    flags: c_int,
+) -> io::Result<libc::sigaction> {
+    let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+    action.sa_sigaction = handler;
+    action.sa_flags = flags;
+
+    let mut old: libc::sigaction = unsafe { std::mem::zeroed() };
+    if unsafe { libc::sigaction(sig, &action, &mut old) } == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(old)
+    }
+}
+
+/// Reset dispositions of all signals (including real-time ones) to `SIG_DFL`.
+///
+/// Signals that can't be changed (`SIGKILL`, `SIGSTOP`, signals reserved by libc) are silently
+/// skipped.
+pub fn reset_all_handlers() -> io::Result<()> {
+    for sig in 1..=libc::SIGRTMAX() {
+        if let Err(err) = set_action(sig, libc::SIG_DFL, 0) {
+            if err.raw_os_error() != Some(libc::EINVAL) {
+                return Err(err);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Give a child process a clean signal slate before `exec`.
+///
+/// Resets all dispositions to `SIG_DFL` and unblocks all signals. Blocked masks and ignored
+/// dispositions are inherited across `exec`, so a child spawned from a process that manipulates
+/// signals should call it, e.g. from [`std::os::unix::process::CommandExt::pre_exec`]. Only
+/// async-signal-safe calls are made.
+pub fn prepare_child_signals() -> io::Result<()> {
+    reset_all_handlers()?;
+    SigSet::new()
+        .set_procmask_untraced(libc::SIG_SETMASK)
+        .map(|_| ())
+}
+
+/// Fork with all signals blocked, running `child` in the child process.
+///
+/// Blocking all signals around `fork` prevents a signal from reaching the child before its
+/// handlers are reset. The child then gets a clean slate (see [`prepare_child_signals`]), runs
+/// `child` and exits with status 0. The parent restores its mask and gets the child's pid.
+///
+/// A panic in `child` never unwinds past the `fork` into the caller's code: the child exits with
+/// status 101 instead (with `std`; otherwise the panic handler decides).
+///
+/// As with any `fork` in a multithreaded program, `child` should stick to async-signal-safe
+/// operations.
+pub fn fork_with_signals_blocked<F: FnOnce()>(child: F) -> io::Result<Pid> {
+    let old = SigSet::full().set_procmask(libc::SIG_SETMASK)?;
+
+    match unsafe { libc::fork() } {
+        -1 => {
+            let err = io::Error::last_os_error();
+            old.set_procmask(libc::SIG_SETMASK)?;
+            Err(err)
+        }
+        0 => {
+            if prepare_child_signals().is_err() {
+                unsafe { libc::_exit(127) };
+            }
+            #[cfg(feature = "std")]
+            let status = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(child)) {
+                Ok(()) => 0,
+                Err(_) => 101,
+            };
+            #[cfg(not(feature = "std"))]
+            let status = {
+                child();
+                0
+            };
+            unsafe { libc::_exit(status) }
+        }
+        pid => {
+            old.set_procmask(libc::SIG_SETMASK)?;
+            Ok(Pid(pid))
+        }
+    }
+}
+
+/// Error for a signal number reported by the kernel but not representable as [`Sig`]
+#[cfg(feature = "std")]
+pub(crate) fn unsupported_signal(sig: c_int) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, InvalidSignal(sig))
+}
+
+/// Error for a signal number reported by the kernel but not representable as [`Sig`].
+///
+/// Without `std` errors only carry a static message, so the number is left out.
+#[cfg(not(feature = "std"))]
+pub(crate) fn unsupported_signal(_sig: c_int) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Unsupported signal received")
+}
+
+/// Run capability `probe` once, caching its result in `cache` (0 means not probed yet).
+pub(crate) fn probe_cached(cache: &AtomicU8, probe: impl FnOnce() -> bool) -> bool {
+    match cache.load(Ordering::Relaxed) {
+        1 => false,
+        2 => true,
+        _ => {
+            let supported = probe();
+            cache.store(if supported { 2 } else { 1 }, Ordering::Relaxed);
+            supported
+        }
+    }
+}
+
+/// Check whether the kernel supports pidfds (`pidfd_open`, Linux 5.3).
+///
+/// Probed once by opening a pidfd of the calling process, the result is cached. A `seccomp`
+/// filter denying the syscall also makes it unsupported.
+pub fn supports_pidfd() -> bool {
+    static SUPPORTED: AtomicU8 = AtomicU8::new(0);
+    probe_cached(&SUPPORTED, || {
+        let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, libc::getpid(), 0) };
+        if fd < 0 {
+            return false;
+        }
+        unsafe { libc::close(fd as c_int) };
+        true
+    })
+}
+
+/// Check whether the calling process is the init process (pid 1) of its PID namespace.
+///
+/// The kernel doesn't deliver signals to init for which it has no handler installed, not even
+/// `SIGTERM` or `SIGINT`, so a process running as init has to catch whatever it wants to react to.
+pub fn is_init_process() -> bool {
+    unsafe { libc::getpid() == 1 }
+}
+
+/// Install `handler` for all `sigs`, the way a process running as init (pid 1) needs it.
+///
+/// Init only gets signals it explicitly handles, so a supervisor running as pid 1 (e.g. as the
+/// entry point of a container) has to catch `SIGTERM`, `SIGINT`, `SIGCHLD` and whatever else it
+/// reacts to. Handlers are installed with `SA_RESTART` and `SA_NOCLDSTOP`, as init typically
+/// reaps children and doesn't care about them being stopped.
+pub fn install_init_handlers(sigs: &[Sig], handler: extern "C" fn(c_int)) -> io::Result<()> {
+    let action = SigAction::handler(handler).flags(libc::SA_RESTART | libc::SA_NOCLDSTOP);
+    for &sig in sigs {
+        action.install(sig)?;
+    }
+    Ok(())
+}
+
+/// Ignore all `sigs` (`SIG_IGN`), returning their previous actions in order.
+///
+/// Meant for the "ignore these at startup" step of a daemon. `SIGKILL` and `SIGSTOP` can't be
+/// ignored, so they are skipped and have no entry in the result (with `tracing` feature enabled,
+/// a warning is emitted for each). Reinstall the returned actions with [`SigAction::install`]
+/// to undo the change. On error, signals processed before the failing one stay ignored.
+pub fn ignore_signals(sigs: &[Sig]) -> io::Result<Vec<SigAction>> {
+    let mut old = Vec::with_capacity(sigs.len());
+    for &sig in sigs {
+        if sig == Sig::KILL || sig == Sig::STOP {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(sig = ?sig, "Signal can't be ignored, skipped");
+            continue;
+        }
+        old.push(SigAction(set_action(sig.into(), libc::SIG_IGN, 0)?));
+    }
+    Ok(old)
+}
+
+/// Run `cmd` and wait for it, with the signal handling of `system(3)`.
+///
+/// While the child runs, `SIGINT` and `SIGQUIT` are ignored and `SIGCHLD` is blocked in the
+/// calling process, so a `^C` on the terminal stops only the child, and no `SIGCHLD` handler
+/// reaps it behind our back. The child gets the original dispositions and mask (through
+/// [`CommandExt::pre_exec`], which is added to `cmd`). Everything is restored before returning,
+/// also on error; if restoring fails, the first error is returned.
+///
+/// Every call adds another `pre_exec` hook to `cmd`, so don't reuse a [`Command`] across calls,
+/// build a new one each time.
+///
+/// Dispositions are process-wide, so other threads don't get `SIGINT` and `SIGQUIT` during the
+/// call either.
+///
+/// [`CommandExt::pre_exec`]: std::os::unix::process::CommandExt::pre_exec
+/// [`Command`]: std::process::Command
+#[cfg(feature = "std")]
+pub fn run_like_system(cmd: &mut std::process::Command) -> io::Result<ExitStatus> {
+    use std::os::unix::process::CommandExt;
+
+    let mask = SigSet::get_current()?;
+    let restore_mask = SigSet::from(&[Sig::CHLD]).block_scoped()?;
+    let int = SigAction(set_action(libc::SIGINT, libc::SIG_IGN, 0)?);
+    let quit = match set_action(libc::SIGQUIT, libc::SIG_IGN, 0) {
+        Ok(quit) => SigAction(quit),
+        Err(err) => {
+            let _ = int.install(Sig::INT);
+            return Err(err);
+        }
+    };
+
+    let mut run = || {
+        let child_mask = mask.clone();
+        unsafe {
+            cmd.pre_exec(move || {
+                int.install(Sig::INT)?;
+                quit.install(Sig::QUIT)?;
+                child_mask.set_mask().map(|_| ())
+            })
+        };
+        let pid = Pid(cmd.spawn()?.id() as pid_t);
+        loop {
+            match pid.waitpid(0) {
+                Ok(status) => {
+                    return Ok(status.expect("waitpid without WNOHANG returned no status"))
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    };
+    let status = run();
+
+    let restored_int = int.install(Sig::INT);
+    let restored_quit = quit.install(Sig::QUIT);
+    let restored_mask = restore_mask.restore();
+    let status = status?;
+    restored_int?;
+    restored_quit?;
+    restored_mask?;
+    Ok(status)
+}
+
+/// Call `handler` on `SIGHUP`, coalescing bursts of signals into a single call.
+///
+/// The handler runs once no further `SIGHUP` arrived for `window`, so a tool sending several
+/// signals in a row triggers one reload, done after the last of them. Signals are received by a
+/// [`SignalThread`] (see [`SigSet::spawn_signal_thread`]), the handler runs in a separate
+/// debouncing thread, which exits after [`SignalThread::shutdown`] (running a reload still
+/// pending at that point).
+#[cfg(feature = "std")]
+pub fn on_reload_debounced(
+    window: Duration,
+    mut handler: impl FnMut() + Send + 'static,
+) -> io::Result<SignalThread> {
+    let (tx, rx) = mpsc::channel();
+    thread::Builder::new()
+        .name("reload".into())
+        .spawn(move || {
+            while rx.recv().is_ok() {
+                // Extend the window for as long as signals keep coming
+                while let Ok(()) = rx.recv_timeout(window) {}
+                handler();
+            }
+        })?;
+    SigSet::from(&[Sig::HUP]).spawn_signal_thread(move |_| {
+        let _ = tx.send(());
+    })
+}
+
+/// Suspend the process the way a shell-managed `^Z` does, and resume custom handling afterwards.
+///
+/// The order matters: the default `SIGTSTP` disposition is restored first and `SIGTSTP` is
+/// unblocked, then it's sent to the whole process group so that the shell notices the job
+/// stopped. Once the process is resumed with `SIGCONT`, the previous mask and disposition are
+/// reinstalled. Call it from the program's own `SIGTSTP` processing (e.g. a signal thread),
+/// after the terminal has been restored to a sane state.
+pub fn suspend_self() -> io::Result<()> {
+    with_default_action(Sig::TSTP, || {
+        let old = SigSet::from(&[Sig::TSTP]).set_procmask(libc::SIG_UNBLOCK)?;
+        let res = if unsafe { libc::kill(0, libc::SIGTSTP) } == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        };
+        old.set_procmask(libc::SIG_SETMASK)?;
+        res
+    })
+}
+
+/// Run `f` with default disposition of `sig`, restoring the previous one afterwards.
+fn with_default_action<T>(sig: Sig, f: impl FnOnce() -> io::Result<T>) -> io::Result<T> {
+    let old = set_action(sig.into(), libc::SIG_DFL, 0)?;
+    let res = f();
+    if unsafe { libc::sigaction(sig.into(), &old, std::ptr::null_mut()) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    res
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    /// Serialize tests changing process-wide signal state (dispositions)
+    fn serial() -> std::sync::MutexGuard<'static, ()> {
+        static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+        LOCK.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// All signals
+    const SIG_ALL: &[Sig] = Sig::ALL;
+
+    #[test]
     fn all_signals() {
         let mut sigset = SigSet::new();
 
-        assert!(!sigset.has_any(SIG_ALL));
+        assert!(!sigset.has_any(SIG_ALL));
+        sigset.fill();
+        assert!(sigset.has_all(SIG_ALL));
+        sigset.clear();
+        assert!(!sigset.has_any(SIG_ALL));
+    }
+
+    #[test]
+    fn single_signal_all_any() {
+        let sigset = SigSet::from(&[Sig::USR1]);
+        assert!(sigset.has(Sig::USR1));
+        assert!(sigset.has_any(SIG_ALL));
+        assert!(!sigset.has_all(SIG_ALL));
+    }
+
+    #[test]
+    fn single_signal() {
+        let mut sigset = SigSet::new();
+
+        for &sig in SIG_ALL {
+            assert!(!sigset.has(sig));
+            sigset.add(sig);
+            assert!(sigset.has(sig));
+            sigset.remove(sig);
+            assert!(!sigset.has(sig));
+        }
+    }
+
+    #[test]
+    fn nice_minus_one_is_not_an_error() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .expect("Can't spawn child");
+        let pid = Pid::from(child.id() as pid_t);
+
+        // Lowering nice below 0 requires CAP_SYS_NICE
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS as _, pid.0 as libc::id_t, -1) } != 0 {
+            eprintln!("Skipping: can't set nice -1 without CAP_SYS_NICE");
+        } else {
+            assert_eq!(pid.nice().unwrap(), -1);
+        }
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    /// Parse a hex signal mask line (like `SigBlk:`) from `/proc/<pid>/status` output
+    fn proc_status_mask(status: &str, field: &str) -> u64 {
+        let line = status
+            .lines()
+            .find(|l| l.starts_with(field))
+            .expect("No such field in status");
+        u64::from_str_radix(line[field.len()..].trim(), 16).unwrap()
+    }
+
+    #[test]
+    fn child_gets_clean_slate() {
+        use std::os::unix::process::CommandExt;
+        let _serial = serial();
+
+        let bit = 1u64 << (libc::SIGWINCH - 1);
+        let old = unsafe { libc::signal(libc::SIGWINCH, libc::SIG_IGN) };
+
+        let mut plain = std::process::Command::new("cat");
+        plain.arg("/proc/self/status");
+        let status = String::from_utf8(plain.output().unwrap().stdout).unwrap();
+        assert_ne!(proc_status_mask(&status, "SigIgn:") & bit, 0);
+
+        let mut clean = std::process::Command::new("cat");
+        clean.arg("/proc/self/status");
+        unsafe { clean.pre_exec(prepare_child_signals) };
+        let status = String::from_utf8(clean.output().unwrap().stdout).unwrap();
+        assert_eq!(proc_status_mask(&status, "SigIgn:") & bit, 0);
+        assert_eq!(proc_status_mask(&status, "SigBlk:"), 0);
+
+        unsafe { libc::signal(libc::SIGWINCH, old) };
+    }
+
+    #[cfg(feature = "rustix")]
+    #[test]
+    fn rustix_conversion() {
+        use rustix::process::Signal;
+
+        assert_eq!(Signal::from(Sig::INT), Signal::INT);
+        #[cfg(not(feature = "posix-only"))]
+        assert_eq!(Signal::from(Sig::POLL), Signal::IO);
+        assert_eq!(Sig::from(Signal::TERM), Sig::TERM);
+        assert_eq!(Sig::from(Signal::CHILD), Sig::CHLD);
+
+        for &sig in SIG_ALL {
+            assert_eq!(Sig::from(Signal::from(sig)), sig);
+        }
+        let rt = Sig::rt(2).unwrap();
+        assert_eq!(Signal::from(rt).as_raw(), libc::SIGRTMIN() + 2);
+        assert_eq!(Sig::from(Signal::from(rt)), rt);
+    }
+
+    #[test]
+    fn collect_into_buffer() {
+        let sigset = SigSet::from(&[Sig::TERM, Sig::INT, Sig::USR1]);
+
+        let mut small = [Sig::HUP; 2];
+        assert_eq!(sigset.collect_into(&mut small), 2);
+        assert_eq!(small, [Sig::INT, Sig::USR1]);
+
+        let mut large = [Sig::HUP; 8];
+        assert_eq!(sigset.collect_into(&mut large), 3);
+        assert_eq!(large[..3], [Sig::INT, Sig::USR1, Sig::TERM]);
+
+        let rt = Sig::rt(2).unwrap();
+        let sigset = SigSet::from(&[Sig::TERM, rt]);
+        assert_eq!(sigset.collect_into(&mut large), 2);
+        assert_eq!(large[..2], [Sig::TERM, rt]);
+    }
+
+    #[test]
+    fn all_blockable() {
+        let sigset = SigSet::all_blockable();
+        assert!(!sigset.has_any(&[Sig::KILL, Sig::STOP]));
+        assert!(sigset.has_all(&[Sig::INT, Sig::TERM, Sig::USR1, Sig::CHLD]));
+    }
+
+    #[test]
+    fn for_suspend_waiting_on() {
+        let wake = [Sig::INT, Sig::TERM, Sig::rt(2).unwrap()];
+        let sigset = SigSet::for_suspend_waiting_on(&wake);
+        assert!(!sigset.has_any(&wake));
+        assert_eq!(sigset, &SigSet::full() - &SigSet::from(&wake));
+        assert_eq!(sigset.count(), SigSet::full().count() - wake.len());
+    }
+
+    #[test]
+    fn eintr_correlates_with_signal() {
+        let _serial = serial();
+
+        Sig::USR2.track_delivery().unwrap();
+
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+
+        let done = Arc::new(AtomicBool::new(false));
+        let worker_done = done.clone();
+        let worker = std::thread::spawn(move || {
+            let mut byte = 0u8;
+            let res = unsafe { libc::read(fds[0], &mut byte as *mut u8 as *mut _, 1) };
+            let err = io::Error::last_os_error();
+            worker_done.store(true, Ordering::SeqCst);
+            (res, err)
+        });
+
+        let thread = worker.as_pthread_t();
+        while !done.load(Ordering::SeqCst) {
+            unsafe { libc::pthread_kill(thread, libc::SIGUSR2) };
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        let (res, err) = worker.join().unwrap();
+        assert_eq!(res, -1);
+        assert_eq!(err.raw_os_error(), Some(libc::EINTR));
+        assert_eq!(last_delivered_signal(), Some(Sig::USR2));
+
+        unsafe {
+            libc::signal(libc::SIGUSR2, libc::SIG_DFL);
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn signal_thread() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let sigset = SigSet::from(&[Sig::USR1]);
+        let signal_thread = sigset
+            .spawn_signal_thread(move |sig| {
+                assert!(current_handled_set().unwrap().has(sig));
+                tx.send(sig).unwrap()
+            })
+            .unwrap();
+
+        let thread = signal_thread.thread.as_pthread_t();
+        unsafe { libc::pthread_kill(thread, libc::SIGUSR1) };
+        assert_eq!(
+            rx.recv_timeout(std::time::Duration::from_secs(5)),
+            Ok(Sig::USR1)
+        );
+
+        signal_thread.shutdown().unwrap();
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn signal_thread_rt_only() {
+        let rt = Sig::rt(4).unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let signal_thread = SigSet::from(&[rt])
+            .spawn_signal_thread(move |sig| tx.send(sig).unwrap())
+            .unwrap();
+
+        let thread = signal_thread.thread.as_pthread_t();
+        unsafe { libc::pthread_kill(thread, rt.into()) };
+        assert_eq!(rx.recv_timeout(std::time::Duration::from_secs(5)), Ok(rt));
+
+        signal_thread.shutdown().unwrap();
+    }
+
+    #[test]
+    fn unknown_kernel_signal_does_not_panic() {
+        let _serial = serial();
+
+        let saved = LAST_DELIVERED.swap(libc::SIGRTMAX() + 1, Ordering::Relaxed);
+        assert_eq!(last_delivered_signal(), None);
+        // Reserved by libc
+        LAST_DELIVERED.store(libc::SIGRTMIN() - 1, Ordering::Relaxed);
+        assert_eq!(last_delivered_signal(), None);
+        LAST_DELIVERED.store(saved, Ordering::Relaxed);
+    }
+
+    #[test]
+    fn reserved_signal_numbers_are_rejected() {
+        // Reserved by libc below `SIGRTMIN`
+        for raw in [32, 33] {
+            // The child is gone by then, so its status is kept rather than rejected
+            assert_eq!(
+                ExitStatus::from_raw(raw).unwrap(),
+                ExitStatus::SignaledRaw {
+                    signo: raw,
+                    core_dumped: false
+                }
+            );
+
+            let mut info: siginfo_t = unsafe { std::mem::zeroed() };
+            info.si_signo = raw;
+            assert_eq!(
+                SigInfo::from_raw(&info).unwrap_err().kind(),
+                io::ErrorKind::InvalidData
+            );
+
+            let mut record: libc::signalfd_siginfo = unsafe { std::mem::zeroed() };
+            record.ssi_signo = raw as u32;
+            let bytes = unsafe {
+                std::slice::from_raw_parts(
+                    &record as *const libc::signalfd_siginfo as *const u8,
+                    std::mem::size_of::<libc::signalfd_siginfo>(),
+                )
+            };
+            let err = decode(bytes).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert_eq!(
+                err.to_string(),
+                format!("invalid or unsupported signal: {}", raw)
+            );
+        }
+    }
+
+    #[test]
+    fn debug_lists_members() {
+        let sigset = SigSet::from(&[Sig::TERM, Sig::INT, Sig::rt(2).unwrap()]);
+        let debug = format!("{:?}", sigset);
+        assert!(debug.contains("INT"));
+        assert_eq!(debug, "SigSet { INT, TERM, RTMIN+2 }");
+        assert_eq!(format!("{:?}", SigSet::new()), "SigSet {}");
+    }
+
+    #[test]
+    fn collect_and_extend() {
+        // Edition 2018 resolves `array.into_iter()` to the slice iterator
+        let mut sigset: SigSet = IntoIterator::into_iter([Sig::INT, Sig::TERM]).collect();
+        assert!(sigset.has_all(&[Sig::INT, Sig::TERM]));
+        assert_eq!(sigset.count(), 2);
+
+        sigset.extend(
+            Sig::all()
+                .iter()
+                .copied()
+                .filter(|sig| sig.name().starts_with("USR")),
+        );
+        assert_eq!(
+            sigset.which_present(SIG_ALL),
+            vec![Sig::INT, Sig::TERM, Sig::USR1, Sig::USR2]
+        );
+
+        let without_int: SigSet = sigset.iter().filter(|&sig| sig != Sig::INT).collect();
+        assert_eq!(without_int.count(), 3);
+    }
+
+    #[test]
+    fn default_is_empty() {
+        #[derive(Default)]
+        struct Config {
+            mask: SigSet,
+        }
+
+        assert!(SigSet::default().is_empty());
+        assert_eq!(Config::default().mask, SigSet::new());
+    }
+
+    #[test]
+    fn content_hash() {
+        let mut built = SigSet::new();
+        built.add(Sig::TERM);
+        built.add(Sig::INT);
+        let listed = SigSet::from(&[Sig::INT, Sig::TERM]);
+        assert_eq!(built, listed);
+        assert_eq!(built.content_hash(), listed.content_hash());
+
+        let rt = SigSet::from(&[Sig::rt(0).unwrap()]);
+        let hashes = [
+            SigSet::new().content_hash(),
+            SigSet::full().content_hash(),
+            SigSet::from(&[Sig::INT]).content_hash(),
+            listed.content_hash(),
+            rt.content_hash(),
+        ];
+        for (i, hash) in hashes.iter().enumerate() {
+            assert!(!hashes[i + 1..].contains(hash));
+        }
+    }
+
+    #[test]
+    fn block_now() {
+        let before = SigSet::get_current().unwrap();
+        let old = SigSet::from(&[Sig::USR1, Sig::USR2]).block_now().unwrap();
+        assert_eq!(old, before);
+
+        let current = SigSet::get_current().unwrap();
+        assert!(current.has(Sig::USR1) && current.has(Sig::USR2));
+
+        old.set_mask().unwrap();
+        assert_eq!(SigSet::get_current().unwrap(), before);
+    }
+
+    #[test]
+    fn wait() {
+        let (tx, rx) = mpsc::channel();
+        let waiter = thread::spawn(move || {
+            let sigset = SigSet::from(&[Sig::USR2]);
+            sigset.disable_default_handler().unwrap();
+            tx.send(unsafe { libc::pthread_self() }).unwrap();
+            sigset.wait()
+        });
+
+        let waiter_thread = rx.recv().unwrap();
+        unsafe { libc::pthread_kill(waiter_thread, libc::SIGUSR2) };
+        assert_eq!(waiter.join().unwrap().unwrap(), Sig::USR2);
+    }
+
+    #[test]
+    fn wait_info() {
+        let sigset = SigSet::from(&[Sig::USR1]);
+        let _guard = sigset.block_scoped().unwrap();
+        unsafe { libc::raise(libc::SIGUSR1) };
+
+        let info = sigset.wait_info().unwrap();
+        assert_eq!(info.sig, Sig::USR1);
+        assert_eq!(info.pid, Pid::own().unwrap());
+        assert_eq!(info.uid, unsafe { libc::getuid() });
+        // Sent from user space (`SI_USER`/`SI_TKILL`, depending on the kernel)
+        assert!(info.code <= 0);
+    }
+
+    #[test]
+    fn send_to_group() {
+        let own = Pgid::own();
+        assert_eq!(pid_t::from(own), unsafe { libc::getpgrp() });
+        // Harmless for every process in the group, cargo included
+        own.send(Sig::CONT).unwrap();
+
+        for pgid in [0, 1, -1] {
+            let err = Pgid::from(pgid).send(Sig::CONT).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn is_alive() {
+        assert!(Pid::own().unwrap().is_alive().unwrap());
+        assert!(!Pid::from(i32::MAX).is_alive().unwrap());
+        for pid in [Pid::ANY_CHILD, Pid::CALLER_GROUP] {
+            assert_eq!(
+                pid.is_alive().unwrap_err().kind(),
+                io::ErrorKind::InvalidInput
+            );
+        }
+    }
+
+    #[test]
+    fn wait_child() {
+        let child = fork_with_signals_blocked(|| unsafe { libc::_exit(7) }).unwrap();
+        assert_eq!(child.wait().unwrap(), ExitStatus::Exited(7));
+
+        let child = fork_with_signals_blocked(|| loop {
+            unsafe { libc::pause() };
+        })
+        .unwrap();
+        assert_eq!(child.try_wait().unwrap(), None);
+        child.send(Sig::STOP).unwrap();
+        assert_eq!(child.wait().unwrap(), ExitStatus::Stopped(Sig::STOP));
+        child.send(Sig::KILL).unwrap();
+        assert_eq!(
+            child.wait().unwrap(),
+            ExitStatus::Signaled {
+                sig: Sig::KILL,
+                core_dumped: false
+            }
+        );
+        assert_eq!(
+            child.try_wait().unwrap_err().raw_os_error(),
+            Some(libc::ECHILD)
+        );
+    }
+
+    #[test]
+    fn fork_child_panics() {
+        let child = fork_with_signals_blocked(|| panic!("Child panicked")).unwrap();
+        assert_eq!(child.wait().unwrap(), ExitStatus::Exited(101));
+    }
+
+    #[test]
+    fn wait_rusage() {
+        let child = fork_with_signals_blocked(|| {
+            // Burn some CPU time in user mode
+            let mut now: libc::timespec = unsafe { std::mem::zeroed() };
+            let mut x = 0u64;
+            while now.tv_nsec < 50_000_000 && now.tv_sec == 0 {
+                for _ in 0..10_000 {
+                    x = std::hint::black_box(x.wrapping_mul(31).wrapping_add(7));
+                }
+                unsafe { libc::clock_gettime(libc::CLOCK_PROCESS_CPUTIME_ID, &mut now) };
+            }
+        })
+        .unwrap();
+
+        let (status, usage) = child.wait_rusage().unwrap();
+        assert_eq!(status, ExitStatus::Exited(0));
+        assert!(usage.user_time > Duration::ZERO);
+        assert!(usage.max_rss_kb > 0);
+    }
+
+    #[test]
+    fn describe_exit_status() {
+        assert_eq!(ExitStatus::Exited(0).describe(), "exited with code 0");
+        assert_eq!(
+            ExitStatus::Signaled {
+                sig: Sig::TERM,
+                core_dumped: false
+            }
+            .describe(),
+            "killed by SIGTERM"
+        );
+        assert_eq!(
+            ExitStatus::Signaled {
+                sig: Sig::rt(2).unwrap(),
+                core_dumped: false
+            }
+            .describe(),
+            "killed by SIGRTMIN+2"
+        );
+        let dumped = ExitStatus::from_raw(libc::SIGSEGV | 0x80).unwrap();
+        assert_eq!(
+            dumped,
+            ExitStatus::Signaled {
+                sig: Sig::SEGV,
+                core_dumped: true
+            }
+        );
+        assert_eq!(dumped.describe(), "killed by SIGSEGV (core dumped)");
+        assert_eq!(
+            ExitStatus::Stopped(Sig::STOP).describe(),
+            "stopped by SIGSTOP"
+        );
+        assert_eq!(
+            ExitStatus::SignaledRaw {
+                signo: 32,
+                core_dumped: false
+            }
+            .describe(),
+            "killed by signal 32"
+        );
+        // `WIFSTOPPED` status: 0x7f in the low byte, the signal above it
+        assert_eq!(
+            ExitStatus::from_raw(33 << 8 | 0x7f).unwrap().describe(),
+            "stopped by signal 33"
+        );
+    }
+
+    #[test]
+    fn wait_child_killed_by_trap() {
+        let child = fork_with_signals_blocked(|| unsafe {
+            // No core file in the working directory
+            let none = libc::rlimit {
+                rlim_cur: 0,
+                rlim_max: 0,
+            };
+            libc::setrlimit(libc::RLIMIT_CORE, &none);
+            libc::raise(libc::SIGTRAP);
+        })
+        .unwrap();
+        assert!(matches!(
+            child.wait().unwrap(),
+            ExitStatus::Signaled { sig: Sig::TRAP, .. }
+        ));
+    }
+
+    #[test]
+    fn send_value() {
+        // A process-directed signal could hit another test thread, so queue it in a child
+        let child = fork_with_signals_blocked(|| unsafe {
+            let sigset = SigSet::from(&[Sig::USR1]);
+            let ok = sigset.disable_default_handler().is_ok()
+                && Pid::own().and_then(|own| own.send_value(Sig::USR1, 42)).is_ok()
+                && matches!(sigset.wait_info(), Ok(info) if info.sig == Sig::USR1 && info.value == 42)
+                // The kernel reports `sival_int` as `ssi_int`, like a C receiver would see it
+                && Pid::own().and_then(|own| own.send_value(Sig::USR1, -42)).is_ok()
+                && matches!(
+                    SignalFd::new(&sigset).and_then(|mut sfd| sfd.recv()),
+                    Ok(info) if info.int == -42
+                );
+            libc::_exit(if ok { 0 } else { 1 });
+        })
+        .unwrap();
+
+        let mut status = 0;
+        unsafe { libc::waitpid(child.into(), &mut status, 0) };
+        assert!(libc::WIFEXITED(status));
+        assert_eq!(libc::WEXITSTATUS(status), 0);
+
+        assert_eq!(
+            Pid::CALLER_GROUP
+                .send_value(Sig::USR1, 1)
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn serve() {
+        // Queued signals are process-directed, so serve them in a single-threaded child
+        let child = fork_with_signals_blocked(|| unsafe {
+            let rt = Sig::rt(1).unwrap();
+            let sigset = SigSet::from(&[rt]);
+            let own = Pid::own().unwrap();
+            if sigset.block_now().is_err() || (1..=4).any(|n| own.send_value(rt, n).is_err()) {
+                libc::_exit(1);
+            }
+
+            let mut values = [0; 3];
+            let mut received = 0;
+            let served = sigset.serve(|info| {
+                values[received] = info.value;
+                received += 1;
+                if received == 3 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            });
+            // The fourth one stays queued
+            let ok = served.is_ok()
+                && values == [1, 2, 3]
+                && matches!(sigset.wait_info(), Ok(info) if info.value == 4);
+            libc::_exit(if ok { 0 } else { 2 });
+        })
+        .unwrap();
+
+        assert_eq!(child.wait().unwrap(), ExitStatus::Exited(0));
+    }
+
+    #[test]
+    fn wait_checked() {
+        let sigset = SigSet::from(&[Sig::USR2]);
+        let err = sigset.wait_checked().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        sigset.disable_default_handler().unwrap();
+        unsafe { libc::raise(libc::SIGUSR2) };
+        assert_eq!(sigset.wait_checked().unwrap(), Sig::USR2);
+        sigset.enable_default_handler().unwrap();
+    }
+
+    #[test]
+    fn receive_once() {
+        let usr1 = SigSet::from(&[Sig::USR1]);
+        usr1.disable_default_handler().unwrap();
+        unsafe { libc::raise(libc::SIGUSR1) };
+
+        // USR2 is not blocked before the call, so it must not stay blocked after it
+        let before = SigSet::get_current().unwrap();
+        let sigset = SigSet::from(&[Sig::USR1, Sig::USR2]);
+        assert_eq!(sigset.receive_once().unwrap(), Sig::USR1);
+        let after = SigSet::get_current().unwrap();
+        assert_eq!(before.which_present(SIG_ALL), after.which_present(SIG_ALL));
+        assert!(!after.has(Sig::USR2));
+
+        usr1.enable_default_handler().unwrap();
+    }
+
+    #[test]
+    fn handler_mask() {
+        extern "C" fn handler(_: c_int) {}
+
+        let _serial = serial();
+        let mask = SigSet::from(&[Sig::USR2, Sig::HUP]);
+        let old = SigAction::handler(handler)
+            .flags(libc::SA_RESTART)
+            .mask(&mask)
+            .install(Sig::USR1)
+            .unwrap();
+
+        let installed = old.install(Sig::USR1).unwrap();
+        assert_eq!(
+            installed.blocked().which_present(SIG_ALL),
+            vec![Sig::HUP, Sig::USR2]
+        );
+    }
+
+    #[test]
+    fn not_init_process() {
+        // Tests run under cargo, never as pid 1
+        assert!(!is_init_process());
+    }
+
+    #[test]
+    fn init_handlers_in_pid_namespace() {
+        static CAUGHT: AtomicBool = AtomicBool::new(false);
+        extern "C" fn handler(_: c_int) {
+            CAUGHT.store(true, Ordering::SeqCst);
+        }
+
+        let child = fork_with_signals_blocked(|| unsafe {
+            // Needs privileges, skip when not available
+            if libc::unshare(libc::CLONE_NEWPID) == -1 {
+                libc::_exit(77);
+            }
+            let init = libc::fork();
+            if init == 0 {
+                let ok = is_init_process()
+                    && Sig::TERM.will_be_ignored_as_init()
+                    && {
+                        // Dropped by the kernel, despite the default action being termination
+                        libc::kill(1, libc::SIGTERM);
+                        install_init_handlers(&[Sig::TERM], handler).is_ok()
+                    }
+                    && !Sig::TERM.will_be_ignored_as_init()
+                    && {
+                        libc::kill(1, libc::SIGTERM);
+                        CAUGHT.load(Ordering::SeqCst)
+                    };
+                libc::_exit(if ok { 0 } else { 1 });
+            }
+            let mut status = 0;
+            libc::waitpid(init, &mut status, 0);
+            libc::_exit(if libc::WIFEXITED(status) {
+                libc::WEXITSTATUS(status)
+            } else {
+                2
+            });
+        })
+        .unwrap();
+
+        let mut status = 0;
+        unsafe { libc::waitpid(child.into(), &mut status, 0) };
+        assert!(libc::WIFEXITED(status));
+        let code = libc::WEXITSTATUS(status);
+        assert!(code == 0 || code == 77, "init check failed with {}", code);
+    }
+
+    #[test]
+    fn disposition_snapshot() {
+        // Ignoring discards pending signals in every thread, so use one no test waits for
+        let _serial = serial();
+        let snapshot = DispositionSnapshot::capture().unwrap();
+        let before = Sig::VTALRM.current_disposition().unwrap();
+
+        set_action(libc::SIGVTALRM, libc::SIG_IGN, 0).unwrap();
+        assert_eq!(
+            Sig::VTALRM.current_disposition().unwrap(),
+            Disposition::Ignore
+        );
+
+        snapshot.restore().unwrap();
+        assert_eq!(Sig::VTALRM.current_disposition().unwrap(), before);
+    }
+
+    #[test]
+    fn try_from_i32() {
+        assert_eq!(Sig::try_from(libc::SIGTERM), Ok(Sig::TERM));
+
+        let err = Sig::try_from(libc::SIGRTMAX() + 1).unwrap_err();
+        assert_eq!(err.0, libc::SIGRTMAX() + 1);
+        assert_eq!(
+            err.to_string(),
+            format!("invalid or unsupported signal: {}", err.0)
+        );
+        assert!(Sig::try_from(0).is_err());
+        assert!(Sig::try_from(-1).is_err());
+    }
+
+    #[test]
+    fn is_supported() {
+        for sig in SIG_ALL {
+            // Everything in `Sig` is compiled in for this target, so it must be supported
+            assert!(sig.is_supported(), "{:?}", sig);
+        }
+    }
+
+    #[test]
+    fn rt_signals() {
+        let last = (libc::SIGRTMAX() - libc::SIGRTMIN()) as u8;
+        let rtmin = Sig::rt(0).unwrap();
+        let rt3 = Sig::rt(3).unwrap();
+        assert_eq!(i32::from(rtmin), libc::SIGRTMIN());
+        assert_eq!(Sig::from(libc::SIGRTMIN() + 3), rt3);
+        assert_eq!(Sig::from(i32::from(rt3)), rt3);
+        assert_eq!(Sig::rt(last).map(i32::from), Some(libc::SIGRTMAX()));
+        assert_eq!(
+            Sig::rt(last).map(|sig| match sig {
+                Sig::RT(offset) => offset.get(),
+                _ => unreachable!(),
+            }),
+            Some(last)
+        );
+        assert_eq!(Sig::rt(last + 1), None);
+        assert_eq!(Sig::from_wire(rt3.to_wire()), Some(rt3));
+
+        let mut sigset = SigSet::new();
+        sigset.add(rt3);
+        assert!(sigset.has(rt3));
+        assert!(!sigset.has(rtmin));
+
+        sigset.disable_default_handler().unwrap();
+        unsafe { libc::raise(libc::SIGRTMIN() + 3) };
+        assert_eq!(sigset.wait_checked().unwrap(), rt3);
+        sigset.enable_default_handler().unwrap();
+    }
+
+    #[test]
+    fn capability_probes() {
+        let pidfd = supports_pidfd();
+        // Cached from now on
+        assert_eq!(supports_pidfd(), pidfd);
+        assert!(supports_signalfd());
+    }
+
+    #[test]
+    fn all() {
+        let expected = if cfg!(feature = "posix-only") { 28 } else { 31 };
+        assert_eq!(Sig::all().len(), expected);
+        assert!(Sig::all().contains(&Sig::INT));
+
+        let rt_count = (libc::SIGRTMAX() - libc::SIGRTMIN() + 1) as usize;
+        let with_rt: Vec<_> = Sig::all_including_rt().collect();
+        assert_eq!(with_rt.len(), expected + rt_count);
+        assert_eq!(with_rt.last().copied(), Sig::from_raw(libc::SIGRTMAX()));
+    }
+
+    #[test]
+    fn name_and_description() {
+        assert_eq!(Sig::SEGV.name(), "SEGV");
+        assert_eq!(Sig::SEGV.description(), "Segmentation fault");
+        assert_eq!(Sig::rt(1).unwrap().name(), "RT");
+        for &sig in SIG_ALL {
+            assert!(!sig.description().is_empty());
+            assert_eq!(sig.name().parse::<Sig>().unwrap(), sig);
+        }
+    }
+
+    #[test]
+    fn parse_and_display() {
+        assert_eq!("SIGINT".parse::<Sig>(), Ok(Sig::INT));
+        assert_eq!("int".parse::<Sig>(), Ok(Sig::INT));
+        assert_eq!("2".parse::<Sig>(), Ok(Sig::INT));
+        assert_eq!("SigRtMin+3".parse::<Sig>(), Ok(Sig::rt(3).unwrap()));
+        assert_eq!("FOO".parse::<Sig>(), Err(ParseSigError("FOO".to_owned())));
+        assert!("0".parse::<Sig>().is_err());
+        assert!("RTMIN+255".parse::<Sig>().is_err());
+
+        assert_eq!(Sig::TERM.to_string(), "TERM");
+        assert_eq!(Sig::rt(3).unwrap().to_string(), "RTMIN+3");
+        for &sig in SIG_ALL {
+            assert_eq!(sig.to_string().parse::<Sig>(), Ok(sig));
+        }
+    }
+
+    #[test]
+    fn iter() {
+        let sigset = SigSet::full();
+        let sigs: Vec<_> = sigset.iter().collect();
+        let mut all = SIG_ALL.to_vec();
+        all.sort_by_key(|&sig| i32::from(sig));
+        assert_eq!(sigs[..all.len()], all[..]);
+        assert_eq!(
+            sigs.last(),
+            Sig::rt((libc::SIGRTMAX() - libc::SIGRTMIN()) as u8).as_ref()
+        );
+
+        let sigset = SigSet::from(&[Sig::TERM, Sig::INT]);
+        let sigs: Vec<_> = (&sigset).into_iter().collect();
+        assert_eq!(sigs, vec![Sig::INT, Sig::TERM]);
+    }
+
+    #[test]
+    fn transition_to() {
+        let _restore = SigBlockGuard(SigSet::get_current().unwrap(), PhantomData);
+        let current = SigSet::from(&[Sig::HUP, Sig::USR1]);
+        let target = SigSet::from(&[Sig::USR1, Sig::USR2, Sig::WINCH]);
+
+        let (to_block, to_unblock) = current.transition_to(&target);
+        assert_eq!(
+            to_block.iter().collect::<Vec<_>>(),
+            vec![Sig::USR2, Sig::WINCH]
+        );
+        assert_eq!(to_unblock.iter().collect::<Vec<_>>(), vec![Sig::HUP]);
+
+        current.set_procmask(libc::SIG_SETMASK).unwrap();
+        to_block.disable_default_handler().unwrap();
+        to_unblock.enable_default_handler().unwrap();
+        assert_eq!(
+            SigSet::get_current().unwrap().iter().collect::<Vec<_>>(),
+            target.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn set_operators() {
+        let sigs = |sigset: &SigSet| sigset.iter().collect::<Vec<_>>();
+        let a = SigSet::from(&[Sig::HUP, Sig::INT, Sig::USR1]);
+        let b = SigSet::from(&[Sig::INT, Sig::USR1, Sig::TERM]);
+
+        assert_eq!(
+            sigs(&(&a | &b)),
+            vec![Sig::HUP, Sig::INT, Sig::USR1, Sig::TERM]
+        );
+        assert_eq!(sigs(&(&a & &b)), vec![Sig::INT, Sig::USR1]);
+        assert_eq!(sigs(&(&a - &b)), vec![Sig::HUP]);
+        assert_eq!(sigs(&(&a ^ &b)), vec![Sig::HUP, Sig::TERM]);
+
+        let mut c = a | SigSet::from(&[Sig::WINCH]);
+        c -= &b;
+        assert_eq!(sigs(&c), vec![Sig::HUP, Sig::WINCH]);
+        c &= SigSet::from(&[Sig::WINCH]);
+        assert_eq!(sigs(&c), vec![Sig::WINCH]);
+    }
+
+    #[test]
+    fn reconcile() {
+        let _restore = SigBlockGuard(SigSet::get_current().unwrap(), PhantomData);
+        SigSet::from(&[Sig::HUP, Sig::USR1])
+            .disable_default_handler()
+            .unwrap();
+
+        let target = SigSet::from(&[Sig::USR1, Sig::USR2]);
+        SigSet::reconcile(&target).unwrap();
+        assert_eq!(
+            SigSet::get_current().unwrap().iter().collect::<Vec<_>>(),
+            target.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn clone_and_eq() {
+        let sigset = SigSet::from(&[Sig::INT, Sig::TERM, Sig::rt(2).unwrap()]);
+        let mut copy = sigset.clone();
+        assert_eq!(copy, sigset);
+
+        copy.remove(Sig::rt(2).unwrap());
+        assert!(copy != sigset);
+        assert_eq!(SigSet::new(), SigSet::new());
+    }
+
+    #[test]
+    fn count_and_is_empty() {
+        let mut sigset = SigSet::new();
+        assert_eq!(sigset.count(), 0);
+        assert!(sigset.is_empty());
+
         sigset.fill();
-        assert!(sigset.has_all(SIG_ALL));
+        // Signals reserved by libc are neither in a full set, nor can they be queried
+        let valid = (1..=libc::SIGRTMAX())
+            .filter(|&sig| get_action(sig).is_ok())
+            .count();
+        assert_eq!(sigset.count(), valid);
+        assert!(!sigset.is_empty());
+
         sigset.clear();
-        assert!(!sigset.has_any(SIG_ALL));
+        sigset.add(Sig::rt(0).unwrap());
+        assert_eq!(sigset.count(), 1);
+        assert!(!sigset.is_empty());
     }
 
     #[test]
-    fn single_signal_all_any() {
-        let sigset = SigSet::from(&[Sig::USR1]);
-        assert!(sigset.has(Sig::USR1));
-        assert!(sigset.has_any(SIG_ALL));
-        assert!(!sigset.has_all(SIG_ALL));
+    fn run_like_system() {
+        let _serial = serial();
+        let int = get_action(libc::SIGINT).unwrap();
+        let quit = get_action(libc::SIGQUIT).unwrap();
+        let mask = SigSet::get_current().unwrap();
+
+        let status = super::run_like_system(&mut std::process::Command::new("/bin/true"));
+        assert_eq!(status.unwrap(), ExitStatus::Exited(0));
+
+        // The child gets the original (default) disposition back
+        let status = super::run_like_system(
+            std::process::Command::new("/bin/sh").args(["-c", "kill -INT $$"]),
+        );
+        assert_eq!(
+            status.unwrap(),
+            ExitStatus::Signaled {
+                sig: Sig::INT,
+                core_dumped: false
+            }
+        );
+
+        assert!(super::run_like_system(&mut std::process::Command::new("/nonexistent")).is_err());
+
+        assert_eq!(
+            get_action(libc::SIGINT).unwrap().sa_sigaction,
+            int.sa_sigaction
+        );
+        assert_eq!(
+            get_action(libc::SIGQUIT).unwrap().sa_sigaction,
+            quit.sa_sigaction
+        );
+        assert_eq!(SigSet::get_current().unwrap(), mask);
     }
 
     #[test]
-    fn single_signal() {
+    fn reload_debounced() {
+        let reloads = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = reloads.clone();
+        let reloader = on_reload_debounced(Duration::from_millis(100), move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+
+        for _ in 0..3 {
+            unsafe { libc::pthread_kill(reloader.thread.as_pthread_t(), libc::SIGHUP) };
+            thread::sleep(Duration::from_millis(10));
+        }
+        thread::sleep(Duration::from_millis(400));
+        assert_eq!(reloads.load(Ordering::SeqCst), 1);
+
+        reloader.shutdown().unwrap();
+    }
+
+    #[test]
+    fn get_current() {
+        let usr1 = SigSet::from(&[Sig::USR1]);
+        usr1.disable_default_handler().unwrap();
+        assert!(SigSet::get_current().unwrap().has(Sig::USR1));
+
+        usr1.enable_default_handler().unwrap();
+        assert!(!SigSet::get_current().unwrap().has(Sig::USR1));
+    }
+
+    #[test]
+    fn block_scoped() {
+        let sigset = SigSet::from(&[Sig::USR2]);
+        {
+            let _guard = sigset.block_scoped().unwrap();
+            assert!(SigSet::get_current().unwrap().has(Sig::USR2));
+        }
+        assert!(!SigSet::get_current().unwrap().has(Sig::USR2));
+    }
+
+    #[test]
+    fn set_mask() {
+        let inherited = SigSet::from(&[Sig::HUP, Sig::USR1]);
+        let saved = inherited.set_mask().unwrap();
+
+        let exact = SigSet::from(&[Sig::USR2]);
+        let old = exact.set_mask().unwrap();
+        assert_eq!(old, inherited);
+        assert_eq!(SigSet::get_current().unwrap(), exact);
+
+        saved.set_mask().unwrap();
+    }
+
+    #[test]
+    fn deliver_now() {
+        static DELIVERED: AtomicBool = AtomicBool::new(false);
+        extern "C" fn handler(_: c_int) {
+            DELIVERED.store(true, Ordering::SeqCst);
+        }
+
+        let _serial = serial();
+        let old = SigAction::handler(handler).install(Sig::USR2).unwrap();
+        let usr2 = SigSet::from(&[Sig::USR2]);
+        usr2.disable_default_handler().unwrap();
+
+        Sig::USR2.deliver_now().unwrap();
+        assert!(DELIVERED.load(Ordering::SeqCst));
+        assert!(SigSet::get_current().unwrap().has(Sig::USR2));
+
+        usr2.enable_default_handler().unwrap();
+        old.install(Sig::USR2).unwrap();
+    }
+
+    #[test]
+    fn set_handler() {
+        static CAUGHT: AtomicBool = AtomicBool::new(false);
+        extern "C" fn handler(_: c_int) {
+            CAUGHT.store(true, Ordering::SeqCst);
+        }
+
+        let _serial = serial();
+        let old = Sig::USR1.set_handler(handler).unwrap();
+        unsafe { libc::raise(libc::SIGUSR1) };
+        assert!(CAUGHT.load(Ordering::SeqCst));
+
+        let installed = Sig::USR1.reset_handler().unwrap();
+        assert_eq!(
+            installed.0.sa_sigaction,
+            handler as extern "C" fn(c_int) as libc::sighandler_t
+        );
+        assert_eq!(
+            Sig::USR1.current_disposition().unwrap(),
+            Disposition::Default
+        );
+        old.install(Sig::USR1).unwrap();
+    }
+
+    #[test]
+    fn ignore_and_reset_default() {
+        let _serial = serial();
+        // The Rust runtime ignores SIGPIPE already, remember it to put it back
+        let old = get_action(libc::SIGPIPE).unwrap();
+
+        Sig::PIPE.ignore().unwrap();
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        unsafe { libc::close(fds[0]) };
+        let written = unsafe { libc::write(fds[1], b"x".as_ptr() as *const _, 1) };
+        assert_eq!(written, -1);
+        assert_eq!(io::Error::last_os_error().raw_os_error(), Some(libc::EPIPE));
+        unsafe { libc::close(fds[1]) };
+
+        Sig::PIPE.reset_default().unwrap();
+        assert_eq!(
+            Sig::PIPE.current_disposition().unwrap(),
+            Disposition::Default
+        );
+        SigAction(old).install(Sig::PIPE).unwrap();
+
+        for sig in [Sig::KILL, Sig::STOP] {
+            assert_eq!(sig.ignore().unwrap_err().raw_os_error(), Some(libc::EINVAL));
+            assert_eq!(
+                sig.reset_default().unwrap_err().raw_os_error(),
+                Some(libc::EINVAL)
+            );
+        }
+    }
+
+    #[test]
+    fn ignore_signals() {
+        // Ignoring discards pending signals in every thread, so stick to ones no test waits for
+        let _serial = serial();
+        let old = super::ignore_signals(&[Sig::WINCH, Sig::KILL, Sig::URG]).unwrap();
+        assert_eq!(old.len(), 2);
+        assert_eq!(
+            Sig::WINCH.current_disposition().unwrap(),
+            Disposition::Ignore
+        );
+        assert_eq!(Sig::URG.current_disposition().unwrap(), Disposition::Ignore);
+
+        old[0].install(Sig::WINCH).unwrap();
+        old[1].install(Sig::URG).unwrap();
+        assert_eq!(
+            Sig::WINCH.current_disposition().unwrap(),
+            Disposition::Default
+        );
+    }
+
+    #[test]
+    fn catch_normally_ignored() {
+        static CAUGHT: AtomicBool = AtomicBool::new(false);
+        extern "C" fn handler(_: c_int) {
+            CAUGHT.store(true, Ordering::SeqCst);
+        }
+
+        let _serial = serial();
+        let old = Sig::URG.catch_normally_ignored(handler).unwrap();
+        Sig::URG.deliver_now().unwrap();
+        assert!(CAUGHT.load(Ordering::SeqCst));
+        old.install(Sig::URG).unwrap();
+
+        match Sig::TERM.catch_normally_ignored(handler) {
+            Err(err) => assert_eq!(err.kind(), io::ErrorKind::InvalidInput),
+            Ok(_) => panic!("Handler installed for SIGTERM"),
+        }
+    }
+
+    #[test]
+    fn kernel_sigset() {
+        let sigset = SigSet::from(&[Sig::INT]);
+        let (bytes, size) = sigset.as_kernel_sigset();
+        assert_eq!(size, (libc::SIGRTMAX() as usize + 1) / 8);
+        assert_eq!(bytes.len(), size);
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        assert_eq!(size, 8);
+        // Bit `n-1` stands for signal `n`
+        assert_eq!(bytes[0], 1 << (libc::SIGINT - 1));
+
+        // The raw syscall checks the size
+        let mut old = SigSet::new();
+        let rc = unsafe {
+            libc::syscall(
+                libc::SYS_rt_sigprocmask,
+                libc::SIG_BLOCK,
+                std::ptr::null::<u8>(),
+                old.as_mut_ptr(),
+                size,
+            )
+        };
+        assert_eq!(rc, 0);
+    }
+
+    #[test]
+    fn pending() {
+        let usr1 = SigSet::from(&[Sig::USR1]);
+        usr1.disable_default_handler().unwrap();
+        unsafe { libc::raise(libc::SIGUSR1) };
+        assert!(SigSet::pending().unwrap().has(Sig::USR1));
+
+        assert_eq!(usr1.wait_checked().unwrap(), Sig::USR1);
+        assert!(!SigSet::pending().unwrap().has(Sig::USR1));
+        usr1.enable_default_handler().unwrap();
+    }
+
+    #[test]
+    fn signal_thread_on_cpu() {
+        // Use the last CPU the tests may run on
+        let mut allowed: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        let size = std::mem::size_of::<libc::cpu_set_t>();
+        assert_eq!(unsafe { libc::sched_getaffinity(0, size, &mut allowed) }, 0);
+        let cpu = (0..libc::CPU_SETSIZE as usize)
+            .rev()
+            .find(|&cpu| unsafe { libc::CPU_ISSET(cpu, &allowed) })
+            .unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let tx = std::sync::Mutex::new(tx);
+        let signal_thread = SigSet::from(&[Sig::USR1])
+            .spawn_signal_thread_on_cpu(cpu, move |_| {
+                let _ = tx.lock().unwrap().send(unsafe { libc::sched_getcpu() });
+            })
+            .unwrap();
+
+        unsafe { libc::pthread_kill(signal_thread.thread.as_pthread_t(), libc::SIGUSR1) };
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(cpu as c_int));
+        signal_thread.shutdown().unwrap();
+
+        match SigSet::from(&[Sig::USR1])
+            .spawn_signal_thread_on_cpu(libc::CPU_SETSIZE as usize, |_| {})
+        {
+            Err(err) => assert_eq!(err.kind(), io::ErrorKind::InvalidInput),
+            Ok(_) => panic!("CPU out of range accepted"),
+        }
+
+        // A CPU outside the process's affinity is rejected by the thread before it starts waiting
+        if let Some(cpu) =
+            (0..libc::CPU_SETSIZE as usize).find(|&cpu| !unsafe { libc::CPU_ISSET(cpu, &allowed) })
+        {
+            match SigSet::from(&[Sig::USR1]).spawn_signal_thread_on_cpu(cpu, |_| {}) {
+                Err(err) => assert_eq!(err.raw_os_error(), Some(libc::EINVAL)),
+                Ok(_) => panic!("CPU outside the affinity accepted"),
+            }
+        }
+    }
+
+    #[test]
+    fn which_present() {
+        let sigset = SigSet::from(&[Sig::INT, Sig::TERM, Sig::HUP]);
+        assert_eq!(
+            sigset.which_present(&[Sig::TERM, Sig::USR1, Sig::INT, Sig::USR2]),
+            vec![Sig::TERM, Sig::INT]
+        );
+        assert!(sigset.which_present(&[Sig::USR1]).is_empty());
+    }
+
+    #[test]
+    fn pselect_mask() {
+        let mask = SigSet::from(&[Sig::USR1]);
+        assert_eq!(mask.as_pselect_mask(), &mask.0 as *const sigset_t);
+
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        let mut pollfd = libc::pollfd {
+            fd: fds[0],
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout = to_timespec(Duration::ZERO);
+        let rc = unsafe { libc::ppoll(&mut pollfd, 1, &timeout, mask.as_pselect_mask()) };
+        assert_eq!(rc, 0);
+
+        let rc = unsafe {
+            libc::pselect(
+                0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                &timeout,
+                mask.as_pselect_mask(),
+            )
+        };
+        assert_eq!(rc, 0);
+
+        unsafe {
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn many_raw_partial_failure() {
         let mut sigset = SigSet::new();
+        assert_eq!(
+            sigset
+                .add_many_raw(&[libc::SIGINT, libc::SIGTERM, 0, libc::SIGHUP])
+                .err(),
+            Some((2, 0))
+        );
+        assert!(sigset.has_all(&[Sig::INT, Sig::TERM]));
+        assert!(!sigset.has(Sig::HUP));
 
-        for &sig in SIG_ALL {
-            assert!(!sigset.has(sig));
-            sigset.add(sig);
-            assert!(sigset.has(sig));
-            sigset.remove(sig);
-            assert!(!sigset.has(sig));
+        assert!(sigset.add_many_raw(&[libc::SIGHUP]).is_ok());
+        assert_eq!(
+            sigset
+                .remove_many_raw(&[libc::SIGINT, 1000, libc::SIGTERM])
+                .err(),
+            Some((1, 1000))
+        );
+        assert!(!sigset.has(Sig::INT));
+        assert!(sigset.has_all(&[Sig::TERM, Sig::HUP]));
+    }
+
+    #[test]
+    fn fatal_signals() {
+        assert!(Sig::TERM.is_fatal_by_default());
+        assert!(Sig::SEGV.is_fatal_by_default());
+        assert!(!Sig::CHLD.is_fatal_by_default());
+        assert!(!Sig::STOP.is_fatal_by_default());
+
+        let _serial = serial();
+        assert!(!Sig::CHLD.is_fatal_now().unwrap());
+        assert!(Sig::XFSZ.is_fatal_now().unwrap());
+        let old = unsafe { libc::signal(libc::SIGXFSZ, libc::SIG_IGN) };
+        assert!(!Sig::XFSZ.is_fatal_now().unwrap());
+        unsafe { libc::signal(libc::SIGXFSZ, old) };
+    }
+
+    #[test]
+    fn drain_unique() {
+        let sigset = SigSet::from(&[Sig::USR1, Sig::USR2]);
+        sigset.disable_default_handler().unwrap();
+
+        unsafe {
+            libc::raise(libc::SIGUSR1);
+            libc::raise(libc::SIGUSR1);
+        }
+        let drained = sigset.drain_unique().unwrap();
+        assert!(drained.has(Sig::USR1));
+        assert!(!drained.has(Sig::USR2));
+        assert!(!sigset
+            .drain_unique()
+            .unwrap()
+            .has_any(&[Sig::USR1, Sig::USR2]));
+
+        sigset.enable_default_handler().unwrap();
+    }
+
+    #[test]
+    fn signals_match_target() {
+        #[cfg(all(
+            not(feature = "posix-only"),
+            any(target_arch = "x86_64", target_arch = "aarch64")
+        ))]
+        {
+            assert_eq!(Sig::ALL.len(), 31);
+            assert!(Sig::ALL.contains(&Sig::STKFLT));
+        }
+        #[cfg(all(
+            not(feature = "posix-only"),
+            any(target_arch = "mips", target_arch = "mips64", target_arch = "sparc64")
+        ))]
+        assert_eq!(Sig::ALL.len(), 30);
+        #[cfg(feature = "posix-only")]
+        {
+            assert_eq!(Sig::ALL.len(), 28);
+            assert_eq!(
+                Sig::try_from(libc::SIGPWR),
+                Err(InvalidSignal(libc::SIGPWR))
+            );
+            assert!("POLL".parse::<Sig>().is_err());
+        }
+
+        for &sig in Sig::ALL {
+            assert_eq!(Sig::from_raw(sig.into()), Some(sig));
+        }
+        let known = (1..libc::SIGRTMIN()).filter_map(Sig::from_raw).count();
+        assert_eq!(known, Sig::ALL.len());
+    }
+
+    #[test]
+    fn is_full() {
+        let mut sigset = SigSet::full();
+        assert!(sigset.is_full());
+        sigset.remove(Sig::HUP);
+        assert!(!sigset.is_full());
+        assert!(!SigSet::new().is_full());
+    }
+
+    #[test]
+    fn wire_round_trip() {
+        for &sig in Sig::ALL {
+            assert_eq!(Sig::from_wire(sig.to_wire()), Some(sig));
+        }
+        assert_eq!(Sig::TERM.to_wire(), 15);
+        assert_eq!(Sig::from_wire(0), None);
+        assert_eq!(Sig::from_wire(5), Some(Sig::TRAP));
+        assert_eq!(Sig::from_wire(32), None);
+    }
+
+    #[test]
+    fn thread_local_handled_set() {
+        assert!(current_handled_set().is_none());
+        set_current_handled_set(Some(SigSet::from(&[Sig::HUP])));
+        assert!(current_handled_set().unwrap().has(Sig::HUP));
+
+        let other = std::thread::spawn(|| current_handled_set().is_none());
+        assert!(other.join().unwrap());
+
+        set_current_handled_set(None);
+        assert!(current_handled_set().is_none());
+    }
+
+    #[test]
+    fn wait_count() {
+        let sigset = SigSet::from(&[Sig::USR1]);
+        sigset.wait_count(Sig::USR1, 0).unwrap();
+
+        sigset.disable_default_handler().unwrap();
+        unsafe { libc::raise(libc::SIGUSR1) };
+
+        let waiter = unsafe { libc::pthread_self() };
+        let sent = Arc::new(AtomicBool::new(false));
+        let sender_sent = sent.clone();
+        let sender = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            sender_sent.store(true, Ordering::SeqCst);
+            unsafe { libc::pthread_kill(waiter, libc::SIGUSR1) };
+        });
+
+        sigset.wait_count(Sig::USR1, 2).unwrap();
+        assert!(sent.load(Ordering::SeqCst));
+
+        sender.join().unwrap();
+        sigset.enable_default_handler().unwrap();
+    }
+
+    #[test]
+    fn from_raw_iter() {
+        let (sigset, rejected) =
+            SigSet::from_raw_iter(vec![libc::SIGINT, -3, libc::SIGTERM, 0, 4096]);
+        assert!(sigset.has_all(&[Sig::INT, Sig::TERM]));
+        assert_eq!(sigset.which_present(Sig::ALL).len(), 2);
+        assert_eq!(rejected, vec![-3, 0, 4096]);
+    }
+
+    #[test]
+    fn is_any_pending() {
+        let sigset = SigSet::from(&[Sig::USR1, Sig::USR2]);
+        sigset.disable_default_handler().unwrap();
+        assert!(!sigset.is_any_pending().unwrap());
+
+        unsafe { libc::raise(libc::SIGUSR2) };
+        assert!(sigset.is_any_pending().unwrap());
+        assert!(!SigSet::from(&[Sig::USR1]).is_any_pending().unwrap());
+
+        sigset.drain_unique().unwrap();
+        assert!(!sigset.is_any_pending().unwrap());
+        sigset.enable_default_handler().unwrap();
+    }
+
+    #[test]
+    fn pid_sentinels() {
+        assert_eq!(pid_t::from(Pid::ANY_CHILD), -1);
+        assert_eq!(pid_t::from(Pid::CALLER_GROUP), 0);
+
+        for pid in [Pid::ANY_CHILD, Pid::CALLER_GROUP, Pid::from(-42)] {
+            let err = pid.send(Sig::CONT).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn all_except() {
+        let sigs: Vec<Sig> = Sig::all_except(&[Sig::KILL, Sig::STOP]).collect();
+        assert_eq!(sigs.len(), Sig::ALL.len() - 2);
+        assert!(!sigs.contains(&Sig::KILL));
+        assert!(!sigs.contains(&Sig::STOP));
+        assert!(sigs.contains(&Sig::TERM));
+    }
+
+    #[test]
+    fn lazy_sigset() {
+        static SHUTDOWN: LazySigSet = sigset!(INT, TERM, HUP);
+        static EMPTY: LazySigSet = sigset!();
+
+        assert_eq!(
+            SHUTDOWN.which_present(SIG_ALL),
+            vec![Sig::HUP, Sig::INT, Sig::TERM]
+        );
+        assert!(std::ptr::eq(SHUTDOWN.get(), SHUTDOWN.get()));
+        assert!(EMPTY.is_empty());
+    }
+
+    #[test]
+    fn wait_timeout() {
+        let sigset = SigSet::from(&[Sig::USR2]);
+        let _guard = sigset.block_scoped().unwrap();
+        assert_eq!(
+            sigset.wait_timeout(Duration::from_millis(50)).unwrap(),
+            None
+        );
+
+        unsafe { libc::raise(libc::SIGUSR2) };
+        assert_eq!(
+            sigset.wait_timeout(Duration::from_millis(50)).unwrap(),
+            Some(Sig::USR2)
+        );
+    }
+
+    #[test]
+    fn wait_timeout_err() {
+        let sigset = SigSet::from(&[Sig::USR2]);
+        sigset.disable_default_handler().unwrap();
+
+        let err = sigset
+            .wait_timeout_err(Duration::from_millis(20))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+        unsafe { libc::raise(libc::SIGUSR2) };
+        assert_eq!(
+            sigset.wait_timeout_err(Duration::from_millis(20)).unwrap(),
+            Sig::USR2
+        );
+        sigset.enable_default_handler().unwrap();
+    }
+
+    #[test]
+    fn ensure_blocked() {
+        assert!(!Sig::PROF.ensure_blocked().unwrap());
+        assert!(Sig::PROF.ensure_blocked().unwrap());
+        SigSet::from(&[Sig::PROF]).enable_default_handler().unwrap();
+    }
+
+    #[test]
+    fn terminal_job_control() {
+        let sigset = SigSet::terminal_job_control();
+        assert!(sigset.has_all(&[Sig::TSTP, Sig::TTIN, Sig::TTOU]));
+        assert!(!sigset.has_any(&[Sig::STOP, Sig::CONT, Sig::INT, Sig::HUP]));
+    }
+
+    #[test]
+    fn foreground_group() {
+        use std::os::unix::io::AsRawFd;
+
+        // Needs a controlling terminal
+        let tty = match std::fs::File::open("/dev/tty") {
+            Ok(tty) => tty,
+            Err(_) => return,
+        };
+        let pgid = Pgid::foreground(tty.as_raw_fd()).unwrap();
+        if pgid == Pgid::from(unsafe { libc::getpgrp() }) {
+            pgid.set_foreground(tty.as_raw_fd()).unwrap();
+            assert_eq!(Pgid::foreground(tty.as_raw_fd()).unwrap(), pgid);
+        }
+    }
+
+    #[test]
+    fn default_action_swap() {
+        let _serial = serial();
+
+        Sig::TSTP.track_delivery().unwrap();
+        let inside = with_default_action(Sig::TSTP, || get_action(libc::SIGTSTP)).unwrap();
+        assert_eq!(inside.sa_sigaction, libc::SIG_DFL);
+        assert_eq!(
+            get_action(libc::SIGTSTP).unwrap().sa_sigaction,
+            record_delivery as extern "C" fn(c_int) as libc::sighandler_t
+        );
+
+        unsafe { libc::signal(libc::SIGTSTP, libc::SIG_DFL) };
+    }
+
+    #[test]
+    fn send_sync() {
+        fn assert_send<T: Send>() {}
+        fn assert_sync<T: Sync>() {}
+
+        assert_send::<Sig>();
+        assert_sync::<Sig>();
+        assert_send::<Pid>();
+        assert_sync::<Pid>();
+        assert_send::<SigSet>();
+        assert_sync::<SigSet>();
+        assert_send::<SignalFd<'static>>();
+        assert_sync::<SignalFd<'static>>();
+        assert_send::<PosixTimer>();
+        assert_sync::<PosixTimer>();
+        assert_send::<SignalThread>();
+
+        // Fails to compile (ambiguous impl) if the type is `Send`
+        trait AmbiguousIfSend<A> {
+            fn check() {}
+        }
+        impl<T: ?Sized> AmbiguousIfSend<()> for T {}
+        struct IsSend;
+        impl<T: ?Sized + Send> AmbiguousIfSend<IsSend> for T {}
+        <SigBlockGuard as AmbiguousIfSend<_>>::check();
+    }
+
+    #[test]
+    fn standard_numbers() {
+        assert_eq!(Sig::HUP.standard_number(), 1);
+        assert_eq!(Sig::INT.standard_number(), 2);
+        assert_eq!(Sig::QUIT.standard_number(), 3);
+        assert_eq!(Sig::ABRT.standard_number(), 6);
+        assert_eq!(Sig::KILL.standard_number(), 9);
+        assert_eq!(Sig::USR1.standard_number(), 10);
+        assert_eq!(Sig::ALRM.standard_number(), 14);
+        assert_eq!(Sig::TERM.standard_number(), 15);
+        assert_eq!(Sig::CHLD.standard_number(), 17);
+    }
+
+    #[cfg(feature = "crossbeam-channel")]
+    #[test]
+    fn forward_to_channel() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let forwarder = Sig::USR1.forward_to_channel(tx).unwrap();
+
+        unsafe { libc::pthread_kill(forwarder.thread.as_pthread_t(), libc::SIGUSR1) };
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(Sig::USR1));
+
+        forwarder.shutdown().unwrap();
+    }
+
+    #[test]
+    fn empty_sets_byte_identical() {
+        fn bytes(sigset: &SigSet) -> &[u8] {
+            unsafe {
+                std::slice::from_raw_parts(
+                    sigset.as_ptr() as *const u8,
+                    std::mem::size_of::<sigset_t>(),
+                )
+            }
+        }
+
+        let a = SigSet::new();
+        let mut b = SigSet::from(&[Sig::INT, Sig::TERM]);
+        b.clear();
+        assert_eq!(bytes(&a), bytes(&b));
+        assert!(bytes(&a).iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn handler_if_default() {
+        extern "C" fn handler(_: c_int) {}
+        let _serial = serial();
+
+        assert_eq!(
+            Sig::WINCH.current_disposition().unwrap(),
+            Disposition::Default
+        );
+        assert!(Sig::WINCH.set_handler_if_default(handler).unwrap());
+        assert_eq!(
+            Sig::WINCH.current_disposition().unwrap(),
+            Disposition::Handler
+        );
+        assert!(!Sig::WINCH.set_handler_if_default(handler).unwrap());
+
+        unsafe { libc::signal(libc::SIGWINCH, libc::SIG_DFL) };
+    }
+
+    #[test]
+    fn fork_child_signals_parent() {
+        let sigset = SigSet::from(&[Sig::USR1]);
+        sigset.disable_default_handler().unwrap();
+
+        let parent = Pid::own().unwrap();
+        let tid = Tid::own();
+        let child = fork_with_signals_blocked(|| unsafe {
+            libc::syscall(libc::SYS_tgkill, parent.0, tid.0, libc::SIGUSR1);
+        })
+        .unwrap();
+
+        assert_eq!(
+            sigset.wait_timeout_err(Duration::from_secs(5)).unwrap(),
+            Sig::USR1
+        );
+
+        let mut status = 0;
+        assert_eq!(unsafe { libc::waitpid(child.0, &mut status, 0) }, child.0);
+        assert!(libc::WIFEXITED(status));
+        assert_eq!(libc::WEXITSTATUS(status), 0);
+
+        sigset.enable_default_handler().unwrap();
+    }
+
+    #[test]
+    fn blocked_count() {
+        let sigset = SigSet::from(&[Sig::USR1, Sig::USR2, Sig::HUP]);
+        let blocked = SigSet::from(&[Sig::USR1, Sig::HUP, Sig::TERM]);
+        blocked.disable_default_handler().unwrap();
+        assert_eq!(sigset.blocked_count().unwrap(), 2);
+        blocked.enable_default_handler().unwrap();
+        assert_eq!(sigset.blocked_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn raw_reexports() {
+        let _serial = serial();
+        let sigset = SigSet::from(&[Sig::USR1]);
+        let mut old: raw::sigset_t = unsafe { std::mem::zeroed() };
+        assert_eq!(
+            unsafe { libc::pthread_sigmask(raw::SIG_BLOCK, sigset.as_ptr(), &mut old) },
+            0
+        );
+        assert_eq!(
+            unsafe { libc::pthread_sigmask(raw::SIG_SETMASK, &old, std::ptr::null_mut()) },
+            0
+        );
+
+        let action: raw::sigaction = get_action(libc::SIGUSR1).unwrap();
+        assert_eq!(action.sa_flags & raw::SA_SIGINFO, 0);
+        assert_eq!(raw::SIG_DFL, libc::SIG_DFL);
+        assert!(raw::SIGRTMIN() < raw::SIGRTMAX());
+    }
+
+    #[test]
+    fn still_default() {
+        let sigint = SigSet::from(&[Sig::INT]);
+        sigint.disable_default_handler().unwrap();
+
+        let still = SigSet::full().still_default().unwrap();
+        assert!(!still.has(Sig::INT));
+        assert!(still.has_all(&[Sig::KILL, Sig::STOP]));
+        assert!(!SigSet::from(&[Sig::INT])
+            .still_default()
+            .unwrap()
+            .has(Sig::INT));
+
+        sigint.enable_default_handler().unwrap();
+        assert!(SigSet::full().still_default().unwrap().has(Sig::INT));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing_send() {
+        use std::sync::atomic::AtomicUsize;
+        use tracing::{span, Event, Metadata, Subscriber};
+
+        struct Counter(Arc<AtomicUsize>);
+
+        impl Subscriber for Counter {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+                span::Id::from_u64(1)
+            }
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+            fn event(&self, event: &Event<'_>) {
+                if event.metadata().target().starts_with("pakr_signals") {
+                    self.0.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+            fn enter(&self, _: &span::Id) {}
+            fn exit(&self, _: &span::Id) {}
         }
+
+        let events = Arc::new(AtomicUsize::new(0));
+        tracing::subscriber::with_default(Counter(events.clone()), || {
+            Pid::own().unwrap().send(Sig::WINCH).unwrap();
+        });
+        assert_eq!(events.load(Ordering::SeqCst), 1);
+
+        // Every way of changing the mask is traced
+        tracing::subscriber::with_default(Counter(events.clone()), || {
+            let old = SigSet::new().block_now().unwrap();
+            old.set_mask().unwrap();
+            Sig::WINCH.ensure_blocked().unwrap();
+            old.set_mask().unwrap();
+        });
+        assert_eq!(events.load(Ordering::SeqCst), 5);
+    }
+}
+
+#[cfg(all(test, not(feature = "std")))]
+mod no_std_tests {
+    use super::*;
+
+    #[test]
+    fn core_operations() {
+        let mut sigset = SigSet::from(&[Sig::USR1]);
+        sigset.add(Sig::rt(1).unwrap());
+        assert_eq!(sigset.count(), 2);
+        assert_eq!("usr1".parse::<Sig>(), Ok(Sig::USR1));
+        assert!(Pid::own().is_ok());
+
+        sigset.disable_default_handler().unwrap();
+        assert!(SigSet::get_current().unwrap().has(Sig::USR1));
+        sigset.enable_default_handler().unwrap();
+
+        let err = Pid::CALLER_GROUP.send(Sig::USR1).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = Pid::from(pid_t::MAX).send(Sig::USR1).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::ESRCH));
     }
 }
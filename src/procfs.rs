@@ -0,0 +1,267 @@
+/*
+
+Copyright (c) 2020 Pawel Kraszewski. All rights reserved.
+
+Redistribution and use in source and binary forms, with or without modification, are permitted
+provided that the following conditions are met:
+
+    1. Redistributions of source code must retain the above copyright notice, this list of
+       conditions and the following disclaimer.
+    2. Redistributions in binary form must reproduce the above copyright notice, this list of
+       conditions and the following disclaimer in the documentation and/or other materials
+       provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE,
+DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER
+IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT
+OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+*/
+
+//! Introspection of signal state through `/proc`.
+
+use std::{fs, io, os::unix::io::RawFd, path::PathBuf};
+
+use crate::{Pid, Sig, SigSet};
+
+/// Signal state of the calling thread as seen by the kernel, see [`proc_signal_status`]
+pub struct ProcSignalStatus {
+    /// Signals pending for the thread (`SigPnd`)
+    pub pending: SigSet,
+    /// Signals blocked by the thread (`SigBlk`)
+    pub blocked: SigSet,
+    /// Ignored signals (`SigIgn`)
+    pub ignored: SigSet,
+    /// Signals with a handler installed (`SigCgt`)
+    pub caught: SigSet,
+}
+
+/// Read signal state of the calling thread from `/proc`.
+///
+/// Masks are per-thread, so `/proc/thread-self/status` is parsed rather than `/proc/self/status`
+/// (which reflects the main thread only). Lets you check effective state against the kernel's
+/// view.
+pub fn proc_signal_status() -> io::Result<ProcSignalStatus> {
+    let status = fs::read_to_string("/proc/thread-self/status")?;
+    let field = |name: &str| -> io::Result<SigSet> {
+        let value = status
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing status field"))?;
+        SigSet::from_proc_hex(value)
+    };
+
+    Ok(ProcSignalStatus {
+        pending: field("SigPnd:")?,
+        blocked: field("SigBlk:")?,
+        ignored: field("SigIgn:")?,
+        caught: field("SigCgt:")?,
+    })
+}
+
+/// Check whether the process runs inside a (non-initial) PID namespace, e.g. in a container.
+///
+/// Based on the `NSpid` field of `/proc/self/status`, which lists the pid in every namespace the
+/// process is visible in; more than one entry means a nested namespace. The answer is only as
+/// good as the mounted `/proc`, which must belong to the process' own namespace.
+pub fn in_pid_namespace() -> io::Result<bool> {
+    let status = fs::read_to_string("/proc/self/status")?;
+    let nspid = status
+        .lines()
+        .find_map(|line| line.strip_prefix("NSpid:"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing status field"))?;
+    Ok(nspid.split_whitespace().count() > 1)
+}
+
+/// Send `sig` to every process named `name`, returning pids that were signaled.
+///
+/// Works like a simple `pkill -x`: the name must match `/proc/<pid>/comm` (which is truncated to
+/// 15 bytes by the kernel) exactly. The calling process is never signaled, and processes that
+/// disappear during the scan are skipped.
+pub fn kill_by_name(name: &str, sig: Sig) -> io::Result<Vec<Pid>> {
+    let own = Pid::own()?;
+    let mut signaled = Vec::new();
+    for pid in find_by_name(name)? {
+        if pid == own {
+            continue;
+        }
+        match pid.send(sig) {
+            Ok(()) => signaled.push(pid),
+            Err(err) if err.raw_os_error() == Some(libc::ESRCH) => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(signaled)
+}
+
+/// Find pids of all processes named `name`, like `pidof`.
+///
+/// Matches `/proc/<pid>/comm` exactly, the same as [`kill_by_name`], but nothing is signaled, so
+/// the result can be inspected before acting. The calling process is included if it matches.
+pub fn find_by_name(name: &str) -> io::Result<Vec<Pid>> {
+    let mut pids = Vec::new();
+    for entry in fs::read_dir("/proc")? {
+        let entry = entry?;
+        let pid = match entry
+            .file_name()
+            .to_str()
+            .and_then(|n| n.parse::<libc::pid_t>().ok())
+        {
+            Some(pid) => pid,
+            None => continue,
+        };
+        // Process may be gone already
+        let comm = match fs::read_to_string(entry.path().join("comm")) {
+            Ok(comm) => comm,
+            Err(_) => continue,
+        };
+        if comm.trim_end_matches('\n') == name {
+            pids.push(Pid::from(pid));
+        }
+    }
+    Ok(pids)
+}
+
+impl Pid {
+    /// Get path of the file open as descriptor `fd` in the process.
+    ///
+    /// Reads the `/proc/<pid>/fd/<fd>` link, which requires the same permissions as `ptrace`
+    /// (so generally own processes only). Sockets, pipes and such are returned as the kernel's
+    /// pseudo-paths, like `socket:[12345]`.
+    pub fn fd_target(self, fd: RawFd) -> io::Result<PathBuf> {
+        fs::read_link(format!("/proc/{}/fd/{}", self.0, fd))
+    }
+}
+
+impl SigSet {
+    /// Create [`SigSet`] from a hex mask, as found in `/proc/<pid>/status`.
+    ///
+    /// Bit `n-1` of the mask represents signal `n`. Masks are 16 hex digits on most
+    /// architectures, but 32 on MIPS (128 signals). Signals reserved by libc or beyond `SIGRTMAX`
+    /// can't be represented and are skipped.
+    pub fn from_proc_hex(hex: &str) -> io::Result<Self> {
+        let mask = u128::from_str_radix(hex.trim(), 16)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let (sigset, _) = Self::from_raw_iter(
+            (0..128)
+                .filter(|bit| mask & (1 << bit) != 0)
+                .map(|bit| bit + 1),
+        );
+        Ok(sigset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fork_with_signals_blocked;
+
+    /// Fork a child renaming itself to `name` and waiting for signals.
+    ///
+    /// Tests run in parallel, so each one should use a distinct name.
+    fn spawn_named_child(name: &'static str) -> Pid {
+        let cname = std::ffi::CString::new(name).unwrap();
+        let child = fork_with_signals_blocked(|| unsafe {
+            libc::prctl(libc::PR_SET_NAME, cname.as_ptr() as libc::c_ulong, 0, 0, 0);
+            loop {
+                libc::pause();
+            }
+        })
+        .unwrap();
+
+        // Wait for the rename
+        while fs::read_to_string(format!("/proc/{}/comm", libc::pid_t::from(child)))
+            .unwrap()
+            .trim_end()
+            != name
+        {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        child
+    }
+
+    /// Reap `child`, returning the signal that terminated it
+    fn reap(child: Pid) -> Option<libc::c_int> {
+        let mut status = 0;
+        unsafe { libc::waitpid(child.into(), &mut status, 0) };
+        if libc::WIFSIGNALED(status) {
+            Some(libc::WTERMSIG(status))
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn from_proc_hex() {
+        let sigset = SigSet::from_proc_hex("0000000000004a02").unwrap();
+        assert_eq!(
+            sigset.which_present(Sig::ALL),
+            vec![Sig::INT, Sig::TERM, Sig::USR1, Sig::USR2]
+        );
+        assert!(SigSet::from_proc_hex("xyz").is_err());
+
+        // MIPS has 128 signals
+        let wide = SigSet::from_proc_hex("00000000000000000000000000004a02").unwrap();
+        assert_eq!(wide, sigset);
+        let wide = SigSet::from_proc_hex("80000000000000000000000000004a02").unwrap();
+        assert_eq!(wide, sigset);
+    }
+
+    #[test]
+    fn blocked_in_proc_status() {
+        let sigset = SigSet::from(&[Sig::USR1]);
+        sigset.disable_default_handler().unwrap();
+        assert!(proc_signal_status().unwrap().blocked.has(Sig::USR1));
+
+        sigset.enable_default_handler().unwrap();
+        assert!(!proc_signal_status().unwrap().blocked.has(Sig::USR1));
+    }
+
+    #[test]
+    fn pid_namespace() {
+        // Either answer is valid, depending on where tests run
+        assert!(in_pid_namespace().is_ok());
+    }
+
+    #[test]
+    fn find_named_child() {
+        let child = spawn_named_child("pakr-sig-find");
+        assert!(find_by_name("pakr-sig-find").unwrap().contains(&child));
+
+        child.send(Sig::KILL).unwrap();
+        assert_eq!(reap(child), Some(libc::SIGKILL));
+    }
+
+    #[test]
+    fn kill_named_child() {
+        let child = spawn_named_child("pakr-sig-kill");
+        assert_eq!(
+            kill_by_name("pakr-sig-kill", Sig::TERM).unwrap(),
+            vec![child]
+        );
+        assert_eq!(reap(child), Some(libc::SIGTERM));
+    }
+
+    #[test]
+    fn fd_target() {
+        let own = Pid::own().unwrap();
+        for fd in 0..=2 {
+            // Whatever the test harness connected them to
+            assert!(!own.fd_target(fd).unwrap().as_os_str().is_empty());
+        }
+
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        let target = own.fd_target(fds[0]).unwrap();
+        assert!(target.to_str().unwrap().starts_with("pipe:["));
+        unsafe {
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+        assert!(own.fd_target(-1).is_err());
+    }
+}
@@ -0,0 +1,468 @@
+/*
+
+Copyright (c) 2020 Pawel Kraszewski. All rights reserved.
+
+Redistribution and use in source and binary forms, with or without modification, are permitted
+provided that the following conditions are met:
+
+    1. Redistributions of source code must retain the above copyright notice, this list of
+       conditions and the following disclaimer.
+    2. Redistributions in binary form must reproduce the above copyright notice, this list of
+       conditions and the following disclaimer in the documentation and/or other materials
+       provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE,
+DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER
+IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT
+OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+*/
+
+//! Receiving signals through `signalfd(2)`.
+
+use std::{
+    future::Future,
+    io,
+    marker::PhantomData,
+    mem,
+    os::unix::io::{AsFd, AsRawFd, BorrowedFd, IntoRawFd, RawFd},
+    pin::Pin,
+    sync::atomic::AtomicU8,
+    task::{Context, Poll, Waker},
+};
+
+use crate::{probe_cached, unsupported_signal, Pid, Sig, SigSet};
+
+/// Size of a single `signalfd_siginfo` record read from a signalfd
+const SIGINFO_SIZE: usize = mem::size_of::<libc::signalfd_siginfo>();
+
+/// A signal received through a [`SignalFd`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SignalInfo {
+    /// Received signal
+    pub sig: Sig,
+    /// Signal code (`si_code`)
+    pub code: i32,
+    /// Sender's pid
+    pub pid: Pid,
+    /// Sender's real uid
+    pub uid: u32,
+    /// Exit status or signal (for `SIGCHLD`)
+    pub status: i32,
+    /// Integer sent with `sigqueue`
+    pub int: i32,
+    /// Pointer sent with `sigqueue`
+    pub ptr: u64,
+}
+
+/// Decode a single `signalfd_siginfo` record read from a signalfd.
+///
+/// `bytes` must be exactly one record long. Records of signals unknown to [`Sig`] are reported as
+/// [`io::ErrorKind::InvalidData`].
+pub fn decode(bytes: &[u8]) -> io::Result<SignalInfo> {
+    if bytes.len() != SIGINFO_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Invalid signalfd_siginfo size",
+        ));
+    }
+    let raw = unsafe { (bytes.as_ptr() as *const libc::signalfd_siginfo).read_unaligned() };
+
+    let signo = raw.ssi_signo as i32;
+    let sig = Sig::try_from(signo).map_err(|_| unsupported_signal(signo))?;
+    Ok(SignalInfo {
+        sig,
+        code: raw.ssi_code,
+        pid: Pid::from(raw.ssi_pid as libc::pid_t),
+        uid: raw.ssi_uid,
+        status: raw.ssi_status,
+        int: raw.ssi_int,
+        ptr: raw.ssi_ptr,
+    })
+}
+
+/// Check whether the kernel supports signalfd with flags (`signalfd4`, Linux 2.6.27).
+///
+/// Probed once by creating a descriptor with `SFD_CLOEXEC` and `SFD_NONBLOCK`, the result is
+/// cached. Lets a program pick a fallback (like [`SigSet::wait`] on a dedicated thread) up
+/// front, rather than getting `ENOSYS` or `EINVAL` from [`SignalFd::new`].
+pub fn supports_signalfd() -> bool {
+    static SUPPORTED: AtomicU8 = AtomicU8::new(0);
+    probe_cached(&SUPPORTED, || {
+        let sigset = SigSet::new();
+        let fd =
+            unsafe { libc::signalfd(-1, sigset.as_ptr(), libc::SFD_CLOEXEC | libc::SFD_NONBLOCK) };
+        if fd == -1 {
+            return false;
+        }
+        unsafe { libc::close(fd) };
+        true
+    })
+}
+
+/// File descriptor receiving signals (see `signalfd(2)`).
+///
+/// Signals read from it must be blocked, otherwise they are delivered the regular way.
+///
+/// The descriptor is created with `FD_CLOEXEC`, so it doesn't leak into `exec`-ed children. Use
+/// [`IntoRawFd::into_raw_fd`] to take it over, e.g. to pass it down deliberately.
+///
+/// It's `Send` and `Sync`: a descriptor can be used from any thread. Note however that a signalfd
+/// only reports signals pending for the process and the thread reading it.
+///
+/// The lifetime ties a descriptor wrapped with [`SignalFd::from_raw_fd_borrowed`] to its owner.
+/// Descriptors created by [`SignalFd::new`] are owned and can be `SignalFd<'static>`.
+pub struct SignalFd<'fd> {
+    fd: RawFd,
+    owned: bool,
+    _borrow: PhantomData<BorrowedFd<'fd>>,
+}
+
+impl SignalFd<'static> {
+    /// Create a new signalfd receiving [`Sig`]nals from [`SigSet`].
+    pub fn new(sigset: &SigSet) -> io::Result<Self> {
+        let fd = unsafe { libc::signalfd(-1, sigset.as_ptr(), libc::SFD_CLOEXEC) };
+        if fd == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(Self {
+                fd,
+                owned: true,
+                _borrow: PhantomData,
+            })
+        }
+    }
+}
+
+impl<'fd> SignalFd<'fd> {
+    /// Wrap a signalfd created elsewhere, without taking ownership of it.
+    ///
+    /// The descriptor is not closed on drop. The returned [`SignalFd`] borrows it, so it can't
+    /// outlive the owner:
+    ///
+    /// ```compile_fail
+    /// use pakr_signals::SignalFd;
+    /// use std::os::unix::io::AsFd;
+    ///
+    /// let file = std::fs::File::open("/dev/null").unwrap();
+    /// let mut sfd = SignalFd::from_raw_fd_borrowed(file.as_fd());
+    /// drop(file);
+    /// let _ = sfd.recv();
+    /// ```
+    pub fn from_raw_fd_borrowed(fd: BorrowedFd<'fd>) -> Self {
+        Self {
+            fd: fd.as_raw_fd(),
+            owned: false,
+            _borrow: PhantomData,
+        }
+    }
+
+    /// Receive a single signal, blocking unless the descriptor is non-blocking.
+    pub fn recv(&mut self) -> io::Result<SignalInfo> {
+        let mut buf = [0u8; SIGINFO_SIZE];
+        let len = unsafe { libc::read(self.fd, buf.as_mut_ptr() as *mut _, SIGINFO_SIZE) };
+        if len == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            decode(&buf[..len as usize])
+        }
+    }
+
+    /// Receive all queued signals, e.g. after epoll reported the descriptor readable.
+    ///
+    /// Reads until the queue is empty, so the descriptor must be non-blocking (`O_NONBLOCK`),
+    /// otherwise [`io::ErrorKind::InvalidInput`] is returned instead of hanging.
+    pub fn drain(&mut self) -> io::Result<Vec<SignalInfo>> {
+        let flags = unsafe { libc::fcntl(self.fd, libc::F_GETFL) };
+        if flags == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        if flags & libc::O_NONBLOCK == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Draining blocking signalfd",
+            ));
+        }
+
+        let mut infos = Vec::new();
+        let mut buf = [0u8; 16 * SIGINFO_SIZE];
+        loop {
+            let len = unsafe { libc::read(self.fd, buf.as_mut_ptr() as *mut _, buf.len()) };
+            if len == -1 {
+                let err = io::Error::last_os_error();
+                match err.kind() {
+                    io::ErrorKind::WouldBlock => return Ok(infos),
+                    io::ErrorKind::Interrupted => continue,
+                    _ => return Err(err),
+                }
+            }
+            for record in buf[..len as usize].chunks(SIGINFO_SIZE) {
+                infos.push(decode(record)?);
+            }
+        }
+    }
+
+    /// Receive a single signal, unless `cancel` becomes readable first.
+    ///
+    /// Returns `Ok(None)` when cancelled; `cancel` (e.g. an eventfd or a pipe) is not read, so it
+    /// stays readable. This allows waking up a thread waiting for signals without sending it a
+    /// real signal.
+    pub fn recv_cancellable(&mut self, cancel: BorrowedFd) -> io::Result<Option<SignalInfo>> {
+        let mut fds = [
+            libc::pollfd {
+                fd: self.fd,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+            libc::pollfd {
+                fd: cancel.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            },
+        ];
+        loop {
+            if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1) } == -1 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(err);
+            }
+            if fds[1].revents != 0 {
+                return Ok(None);
+            }
+            if fds[0].revents != 0 {
+                return self.recv().map(Some);
+            }
+        }
+    }
+}
+
+impl Drop for SignalFd<'_> {
+    fn drop(&mut self) {
+        if self.owned {
+            unsafe {
+                libc::close(self.fd);
+            }
+        }
+    }
+}
+
+impl AsRawFd for SignalFd<'_> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl IntoRawFd for SignalFd<'_> {
+    /// Release the descriptor without closing it.
+    fn into_raw_fd(mut self) -> RawFd {
+        self.owned = false;
+        self.fd
+    }
+}
+
+impl AsFd for SignalFd<'_> {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        unsafe { BorrowedFd::borrow_raw(self.fd) }
+    }
+}
+
+/// A future resolving to the first [`Sig`]nal received from a [`SigSet`], see [`SigSet::future`]
+///
+/// It's backed by a non-blocking signalfd and doesn't depend on any async runtime. Polling only
+/// reads the descriptor, it doesn't arrange for a wakeup: the executor must watch the descriptor
+/// (available through [`AsFd`]) for readability and wake the task then. For the trivial case use
+/// [`SignalFuture::block_on`]. Signals must be blocked, as with [`SignalFd`].
+pub struct SignalFuture {
+    fd: SignalFd<'static>,
+}
+
+impl SignalFuture {
+    /// Create a future resolving on one of [`Sig`]nals from [`SigSet`].
+    pub fn new(sigset: &SigSet) -> io::Result<Self> {
+        let fd =
+            unsafe { libc::signalfd(-1, sigset.as_ptr(), libc::SFD_CLOEXEC | libc::SFD_NONBLOCK) };
+        if fd == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(Self {
+                fd: SignalFd {
+                    fd,
+                    owned: true,
+                    _borrow: PhantomData,
+                },
+            })
+        }
+    }
+
+    /// Run the future to completion on the calling thread, sleeping in `poll(2)` in between.
+    pub fn block_on(mut self) -> io::Result<Sig> {
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(result) = Pin::new(&mut self).poll(&mut cx) {
+                return result;
+            }
+            let mut fds = libc::pollfd {
+                fd: self.fd.fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            if unsafe { libc::poll(&mut fds, 1, -1) } == -1 {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+/// `EAGAIN` is reported as pending and `EINTR` is retried. Any other error, including a signal
+/// unknown to [`Sig`] (see [`decode`]), resolves the future to `Err`.
+impl Future for SignalFuture {
+    type Output = io::Result<Sig>;
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<Sig>> {
+        loop {
+            match self.fd.recv() {
+                Ok(info) => return Poll::Ready(Ok(info.sig)),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Poll::Pending,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Poll::Ready(Err(err)),
+            }
+        }
+    }
+}
+
+impl AsFd for SignalFuture {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+impl SigSet {
+    /// Create a [`SignalFuture`] resolving on one of [`Sig`]nals from [`SigSet`].
+    pub fn future(&self) -> io::Result<SignalFuture> {
+        SignalFuture::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::io::{FromRawFd, OwnedFd};
+
+    #[test]
+    fn decode_foreign_signalfd() {
+        let sigset = SigSet::from(&[Sig::USR1]);
+        sigset.disable_default_handler().unwrap();
+
+        let fd = unsafe { libc::signalfd(-1, sigset.as_ptr(), 0) };
+        assert_ne!(fd, -1);
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        unsafe { libc::raise(libc::SIGUSR1) };
+        let mut buf = [0u8; SIGINFO_SIZE];
+        let len = unsafe { libc::read(fd.as_raw_fd(), buf.as_mut_ptr() as *mut _, buf.len()) };
+        assert_eq!(len as usize, SIGINFO_SIZE);
+        let info = decode(&buf).unwrap();
+        assert_eq!(info.sig, Sig::USR1);
+        assert_eq!(info.pid, Pid::own().unwrap());
+        assert!(decode(&buf[1..]).is_err());
+
+        unsafe { libc::raise(libc::SIGUSR1) };
+        let mut borrowed = SignalFd::from_raw_fd_borrowed(fd.as_fd());
+        assert_eq!(borrowed.recv().unwrap().sig, Sig::USR1);
+        drop(borrowed);
+        assert_ne!(unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GETFD) }, -1);
+
+        sigset.enable_default_handler().unwrap();
+    }
+
+    #[test]
+    fn cloexec_by_default() {
+        let sfd = SignalFd::new(&SigSet::from(&[Sig::USR2])).unwrap();
+        let flags = unsafe { libc::fcntl(sfd.as_raw_fd(), libc::F_GETFD) };
+        assert_ne!(flags & libc::FD_CLOEXEC, 0);
+
+        let fd = unsafe { OwnedFd::from_raw_fd(sfd.into_raw_fd()) };
+        assert_ne!(unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GETFD) }, -1);
+    }
+
+    #[test]
+    fn cancel_recv() {
+        let sigset = SigSet::from(&[Sig::USR2]);
+        sigset.disable_default_handler().unwrap();
+        let mut sfd = SignalFd::new(&sigset).unwrap();
+
+        let cancel = unsafe { OwnedFd::from_raw_fd(libc::eventfd(0, libc::EFD_CLOEXEC)) };
+        let cancel_fd = cancel.as_raw_fd();
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            let one = 1u64;
+            unsafe { libc::write(cancel_fd, &one as *const u64 as *const _, 8) };
+        });
+        assert_eq!(sfd.recv_cancellable(cancel.as_fd()).unwrap(), None);
+        canceller.join().unwrap();
+
+        sigset.enable_default_handler().unwrap();
+    }
+
+    #[test]
+    fn drain() {
+        let rt = Sig::rt(1).unwrap();
+        let sigset = SigSet::from(&[Sig::USR1, Sig::USR2, rt]);
+        sigset.disable_default_handler().unwrap();
+        let mut sfd = SignalFd::new(&sigset).unwrap();
+        assert_eq!(sfd.drain().unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+        let flags = unsafe { libc::fcntl(sfd.as_raw_fd(), libc::F_GETFL) };
+        unsafe { libc::fcntl(sfd.as_raw_fd(), libc::F_SETFL, flags | libc::O_NONBLOCK) };
+        unsafe {
+            libc::raise(libc::SIGUSR1);
+            libc::raise(libc::SIGUSR2);
+            // Real-time signals are queued rather than coalesced
+            libc::raise(rt.into());
+            libc::raise(rt.into());
+        }
+
+        let sigs: Vec<_> = sfd.drain().unwrap().iter().map(|info| info.sig).collect();
+        assert_eq!(sigs, vec![Sig::USR1, Sig::USR2, rt, rt]);
+        assert!(sfd.drain().unwrap().is_empty());
+
+        sigset.enable_default_handler().unwrap();
+    }
+
+    #[test]
+    fn future() {
+        let sigset = SigSet::from(&[Sig::USR2]);
+        let _guard = sigset.block_scoped().unwrap();
+
+        let mut future = sigset.future().unwrap();
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+
+        unsafe { libc::raise(libc::SIGUSR2) };
+        assert_eq!(future.block_on().unwrap(), Sig::USR2);
+    }
+
+    #[cfg(feature = "posix-only")]
+    #[test]
+    fn future_unknown_signal() {
+        let mut sigset = SigSet::new();
+        unsafe { libc::sigaddset(sigset.as_mut_ptr(), libc::SIGPWR) };
+        let _guard = sigset.block_scoped().unwrap();
+
+        let future = sigset.future().unwrap();
+        unsafe { libc::raise(libc::SIGPWR) };
+        assert_eq!(
+            future.block_on().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
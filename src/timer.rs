@@ -0,0 +1,146 @@
+/*
+
+Copyright (c) 2020 Pawel Kraszewski. All rights reserved.
+
+Redistribution and use in source and binary forms, with or without modification, are permitted
+provided that the following conditions are met:
+
+    1. Redistributions of source code must retain the above copyright notice, this list of
+       conditions and the following disclaimer.
+    2. Redistributions in binary form must reproduce the above copyright notice, this list of
+       conditions and the following disclaimer in the documentation and/or other materials
+       provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE,
+DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER
+IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT
+OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+*/
+
+//! POSIX per-process timers delivering signals.
+
+use std::{io, mem, ptr, time::Duration};
+
+use crate::{to_timespec, Sig, Tid};
+
+/// A POSIX timer (see `timer_create(2)`) delivering a [`Sig`]nal on expiration.
+///
+/// The timer is deleted on drop. It's `Send` and `Sync`, as the underlying timer id is
+/// process-wide and can be used from any thread.
+pub struct PosixTimer(libc::timer_t);
+
+unsafe impl Send for PosixTimer {}
+unsafe impl Sync for PosixTimer {}
+
+/// A builder for [`PosixTimer`], see [`PosixTimer::builder`]
+pub struct PosixTimerBuilder {
+    sig: Sig,
+    clock: libc::clockid_t,
+    tid: Option<Tid>,
+}
+
+impl PosixTimer {
+    /// Start building a [`PosixTimer`] delivering `sig` to the process on expiration.
+    pub fn builder(sig: Sig) -> PosixTimerBuilder {
+        PosixTimerBuilder {
+            sig,
+            clock: libc::CLOCK_MONOTONIC,
+            tid: None,
+        }
+    }
+
+    /// Arm the timer to expire after `value` and then every `interval`.
+    ///
+    /// Zero `interval` makes a one-shot timer.
+    pub fn arm(&self, value: Duration, interval: Duration) -> io::Result<()> {
+        let spec = libc::itimerspec {
+            it_interval: to_timespec(interval),
+            it_value: to_timespec(value),
+        };
+        if unsafe { libc::timer_settime(self.0, 0, &spec, ptr::null_mut()) } == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Disarm the timer.
+    pub fn disarm(&self) -> io::Result<()> {
+        self.arm(Duration::ZERO, Duration::ZERO)
+    }
+}
+
+impl Drop for PosixTimer {
+    fn drop(&mut self) {
+        unsafe {
+            libc::timer_delete(self.0);
+        }
+    }
+}
+
+impl PosixTimerBuilder {
+    /// Measure time with `clock` instead of default `CLOCK_MONOTONIC`.
+    pub fn clock(mut self, clock: libc::clockid_t) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Deliver the signal to thread `tid` (with `SIGEV_THREAD_ID`) instead of the whole process.
+    ///
+    /// The thread must belong to the calling process.
+    pub fn notify_thread(mut self, tid: Tid) -> Self {
+        self.tid = Some(tid);
+        self
+    }
+
+    /// Create the [`PosixTimer`]. It's created disarmed.
+    pub fn create(self) -> io::Result<PosixTimer> {
+        let mut sev: libc::sigevent = unsafe { mem::zeroed() };
+        sev.sigev_signo = self.sig.into();
+        match self.tid {
+            Some(tid) => {
+                sev.sigev_notify = libc::SIGEV_THREAD_ID;
+                sev.sigev_notify_thread_id = tid.into();
+            }
+            None => sev.sigev_notify = libc::SIGEV_SIGNAL,
+        }
+
+        let mut timer = ptr::null_mut();
+        if unsafe { libc::timer_create(self.clock, &mut sev, &mut timer) } == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(PosixTimer(timer))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SigSet;
+
+    #[test]
+    fn thread_timer() {
+        let sigset = SigSet::from(&[Sig::ALRM]);
+        sigset.disable_default_handler().unwrap();
+
+        let timer = PosixTimer::builder(Sig::ALRM)
+            .notify_thread(Tid::own())
+            .create()
+            .unwrap();
+        timer
+            .arm(Duration::from_millis(10), Duration::ZERO)
+            .unwrap();
+
+        let timeout = to_timespec(Duration::from_secs(5));
+        let sig = unsafe { libc::sigtimedwait(sigset.as_ptr(), ptr::null_mut(), &timeout) };
+        assert_eq!(sig, libc::SIGALRM);
+
+        sigset.enable_default_handler().unwrap();
+    }
+}